@@ -1,146 +1,180 @@
+use crate::engine::core::commands::ApplicationCommand;
+use crate::engine::core::frame_pacing::FramePacing;
 use crate::engine::core::layer::Layer;
 use crate::engine::core::layerstack::LayerStack;
-use crate::engine::core::window::{WindowProps};
+use crate::engine::core::window::{WindowId, WindowProps};
 use crate::engine::events::{Event};
+use crate::engine::renderer::Renderer;
+use crate::engine::runners::Runner;
+use crate::engine::runners::winit_runner::WinitRunner;
 use log::info;
-use std::cell::{Cell, Ref, RefCell};
-use std::ops::Deref;
-use std::rc::Rc;
-use std::borrow::Borrow;
-use std::sync::Arc;
+use std::collections::HashMap;
 use std::sync::mpsc::{channel, Receiver, Sender};
-use winit::application::ApplicationHandler;
-use winit::dpi::LogicalSize;
-use winit::event::WindowEvent;
-use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
-use winit::window::WindowId;
-use crate::engine::events::ApplicationEvent::{RenderRequested, WindowClosed};
-use crate::engine::events::winit_event_mapper::map_event;
-use crate::engine::renderer::{Renderer, Scene};
-use crate::engine::renderer::wgpu::wgpu_renderer::WgpuRenderer;
-
-type WinitWindow = winit::window::Window;
-
-pub struct Application<'app> {
-    layerstack: LayerStack,
-    renderer: Option<WgpuRenderer<'app>>,
-    window: Option<Arc<WinitWindow>>,
-    events_sender: Sender<Event>,
-    events_receiver: Receiver<Event>,
-    window_props: WindowProps
+use std::time::Duration;
+
+/// Owns the layers of every window and the channels through which events and commands arrive.
+/// How windows, input and frames are driven is up to the `Runner` it is run with.
+pub struct Application {
+    layerstacks: HashMap<WindowId, LayerStack>,
+    next_window_id: u64,
+    events_sender: Sender<(WindowId, Event)>,
+    events_receiver: Receiver<(WindowId, Event)>,
+    commands_sender: Sender<ApplicationCommand>,
+    commands_receiver: Receiver<ApplicationCommand>,
+    window_props: WindowProps,
+    frame_pacing: FramePacing
 }
 
-impl <'app> Application<'app> {
-    
-    pub fn new(window_props: WindowProps) -> Application<'app> {
+impl Application {
+
+    pub fn new(window_props: WindowProps) -> Application {
         let (events_sender, events_receiver) = channel();
+        let (commands_sender, commands_receiver) = channel();
+        let mut layerstacks = HashMap::new();
+        layerstacks.insert(WindowId::MAIN, LayerStack::new());
         Self {
-            layerstack: LayerStack::new(),
-            renderer: None,
-            window: None,
+            layerstacks,
+            next_window_id: WindowId::MAIN.0 + 1,
             events_sender,
             events_receiver,
-            window_props
+            commands_sender,
+            commands_receiver,
+            window_props,
+            frame_pacing: FramePacing::default()
         }
     }
-    
+
+    /// Runs the application in a window, driven by the OS event loop.
     pub fn run(&mut self) {
-        info!("Engine started");
+        self.run_with(WinitRunner);
+    }
 
-        let event_loop = EventLoop::new().unwrap();
-        event_loop.set_control_flow(ControlFlow::Poll);
-        event_loop.run_app(self);
+    /// Runs the application with the given runner, e.g. a `HeadlessRunner` for servers and tests.
+    pub fn run_with<R: Runner>(&mut self, runner: R) {
+        runner.run(self);
     }
 
+    /// Pushes a layer onto the main window.
     pub fn push_layer(&mut self, layer: Box<dyn Layer>) {
-        self.layerstack.push_layer(layer);
+        self.main_layerstack_mut().push_layer(layer);
     }
 
+    /// Pushes an overlay onto the main window.
     pub fn push_overlay(&mut self, overlay: Box<dyn Layer>) {
-        self.layerstack.push_overlay(overlay);
-    }
-
-    fn process_events(&mut self, event_loop: &ActiveEventLoop) {
-        while let Ok(event) = self.events_receiver.try_recv() {
-            info!("Event: {:?}", event);
-            
-            match event {
-                Event::ApplicationEvent(WindowClosed) => self.on_window_closed(event_loop),
-                Event::ApplicationEvent(RenderRequested) => self.on_app_render(),
-                _ => {
-                    // ignore for now
-                }
-            }
+        self.main_layerstack_mut().push_overlay(overlay);
+    }
 
-            let mut event_handled = false;
-            for overlay in self.layerstack.overlays().rev() {
-                if !event_handled {
-                    event_handled = overlay.handle_event(&event)
-                }
-            }
-            for layers in self.layerstack.layers().rev() {
-                if !event_handled {
-                    event_handled = layers.handle_event(&event)
-                }
-            }
-        }
+    /// Returns a sender through which layers can issue commands to the application,
+    /// such as opening windows or changing the cursor.
+    pub fn command_sender(&self) -> Sender<ApplicationCommand> {
+        self.commands_sender.clone()
+    }
+
+    /// Returns a sender through which events can be injected from code,
+    /// tagged with the window they should be delivered to.
+    pub fn event_sender(&self) -> Sender<(WindowId, Event)> {
+        self.events_sender.clone()
+    }
+
+    pub fn window_props(&self) -> &WindowProps {
+        &self.window_props
+    }
+
+    /// Sets how often windows render new frames, unlimited and continuous by default.
+    pub fn set_frame_pacing(&mut self, frame_pacing: FramePacing) {
+        self.frame_pacing = frame_pacing;
     }
 
-    fn update_layers(&mut self) {
-        for layer in self.layerstack.layers() {
-            layer.update()
+    pub fn frame_pacing(&self) -> &FramePacing {
+        &self.frame_pacing
+    }
+
+    pub fn window_ids(&self) -> Vec<WindowId> {
+        self.layerstacks.keys().copied().collect()
+    }
+
+    pub(crate) fn add_layerstack(&mut self, layerstack: LayerStack) -> WindowId {
+        let window_id = WindowId(self.next_window_id);
+        self.next_window_id += 1;
+        self.layerstacks.insert(window_id, layerstack);
+        window_id
+    }
+
+    pub(crate) fn remove_layerstack(&mut self, window_id: WindowId) {
+        self.layerstacks.remove(&window_id);
+    }
+
+    pub(crate) fn send_event(&self, window_id: WindowId, event: Event) {
+        self.events_sender.send((window_id, event));
+    }
+
+    pub(crate) fn receive_event(&self) -> Option<(WindowId, Event)> {
+        let received = self.events_receiver.try_recv().ok();
+        if let Some((window_id, event)) = received.as_ref() {
+            info!("Event: {:?} from window {:?}", event, window_id);
         }
-        for overlay in self.layerstack.overlays() {
-            overlay.update()
+        received
+    }
+
+    pub(crate) fn receive_command(&self) -> Option<ApplicationCommand> {
+        let received = self.commands_receiver.try_recv().ok();
+        if let Some(command) = received.as_ref() {
+            info!("Command: {:?}", command);
         }
+        received
     }
 
-    fn run_renderer(&mut self) {
-        if let Some(renderer) = &mut self.renderer {
-            for layer in self.layerstack.layers() {
-                if let Some(scene) = layer.as_scene() {
-                    renderer.render(scene)
-                }
+    pub(crate) fn dispatch_event(&self, window_id: WindowId, event: &Event) {
+        let Some(layerstack) = self.layerstacks.get(&window_id) else {
+            return;
+        };
+
+        let mut event_handled = false;
+        for overlay in layerstack.overlays().rev() {
+            if !event_handled {
+                event_handled = overlay.handle_window_event(window_id, event)
             }
-            for overlay in self.layerstack.overlays() {
-                if let Some(scene) = overlay.as_scene() {
-                    renderer.render(scene)
-                }
+        }
+        for layers in layerstack.layers().rev() {
+            if !event_handled {
+                event_handled = layers.handle_window_event(window_id, event)
             }
         }
     }
 
-    fn on_app_render(&mut self) {
-        self.update_layers();
-        self.run_renderer();
-        if let Some(window) = self.window.as_ref() {
-            window.request_redraw();
+    pub(crate) fn update_layers(&self, window_id: WindowId, delta_time: Duration) {
+        let Some(layerstack) = self.layerstacks.get(&window_id) else {
+            return;
+        };
+
+        for layer in layerstack.layers() {
+            layer.update(delta_time)
+        }
+        for overlay in layerstack.overlays() {
+            overlay.update(delta_time)
         }
     }
 
-    fn on_window_closed(&self, event_loop: &ActiveEventLoop) {
-        event_loop.exit();
-    }
-}
+    pub(crate) fn render_layers(&self, window_id: WindowId, renderer: &mut dyn Renderer) {
+        let Some(layerstack) = self.layerstacks.get(&window_id) else {
+            return;
+        };
 
-impl <'app> ApplicationHandler for Application<'app> {
-    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        let window_attributes = WinitWindow::default_attributes()
-            .with_title(&self.window_props.title)
-            .with_inner_size(LogicalSize::new(
-                self.window_props.width, 
-                self.window_props.height
-            ));
-        let window = event_loop.create_window(window_attributes).unwrap();
-        let window_rc = Arc::new(window);
-        self.renderer = Some(WgpuRenderer::new(Arc::clone(&window_rc)));
-        self.window = Some(window_rc);
-    }
-
-    fn window_event(&mut self, event_loop: &ActiveEventLoop, id: WindowId, event: WindowEvent) {
-        if let Some(event) = map_event(event) {
-            self.events_sender.send(event);
-            self.process_events(event_loop);
+        renderer.begin_frame();
+        for layer in layerstack.layers() {
+            if let Some(scene) = layer.as_scene() {
+                renderer.render(scene)
+            }
         }
+        for overlay in layerstack.overlays() {
+            if let Some(scene) = overlay.as_scene() {
+                renderer.render(scene)
+            }
+        }
+        renderer.end_frame();
+    }
+
+    fn main_layerstack_mut(&mut self) -> &mut LayerStack {
+        self.layerstacks.entry(WindowId::MAIN).or_default()
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,63 @@
+use std::rc::Rc;
+use crate::engine::core::cursor::{CursorGrabMode, CustomCursor};
+use crate::engine::core::layerstack::LayerStack;
+use crate::engine::core::window::{WindowId, WindowProps};
+use crate::engine::renderer::material::Texture;
+use crate::engine::renderer::post_processing::PostProcessingStack;
+use crate::engine::renderer::texture_region::TextureRegion;
+
+/// Requests that layers can send to the application through its command sender.
+/// Commands that target a window apply to the main window when `window_id` is `None`.
+#[derive(Debug)]
+pub enum ApplicationCommand {
+    OpenWindow {
+        props: WindowProps,
+        layerstack: LayerStack
+    },
+    CloseWindow {
+        window_id: WindowId
+    },
+    /// Renders a new frame, needed to show changes when running in `UpdateMode::Reactive`.
+    RequestRedraw {
+        window_id: Option<WindowId>
+    },
+    SetCursorVisible {
+        window_id: Option<WindowId>,
+        visible: bool
+    },
+    SetCursorGrab {
+        window_id: Option<WindowId>,
+        mode: CursorGrabMode
+    },
+    SetCustomCursor {
+        window_id: Option<WindowId>,
+        cursor: CustomCursor
+    },
+    ResetCursor {
+        window_id: Option<WindowId>
+    },
+    /// Replaces the effects applied to the window's frames after all layers have been drawn.
+    SetPostProcessing {
+        window_id: Option<WindowId>,
+        stack: PostProcessingStack
+    },
+    /// Replaces the pixels of a texture on the window's GPU, e.g. for a procedurally generated map or a fog of war mask.
+    /// The texture's own data is left as it is.
+    SetTexturePixels {
+        window_id: Option<WindowId>,
+        texture: Rc<Texture>,
+        region: Option<TextureRegion>, // the part to replace with the origin at the top left, the whole texture when None
+        pixels: Vec<u8> // RGBA pixels of the region, bottom row first like `Texture::data`
+    },
+    /// Frees the GPU memory of a texture in the window's renderer right away, it is uploaded again if it is drawn later on.
+    UnloadTexture {
+        window_id: Option<WindowId>,
+        texture: Rc<Texture>
+    },
+    /// Limits the GPU memory of the window's textures, in bytes. Textures that weren't drawn for the longest time
+    /// are evicted after a frame when the limit is exceeded. Unlimited when None, which is the default.
+    SetTextureMemoryBudget {
+        window_id: Option<WindowId>,
+        budget: Option<u64>
+    }
+}
@@ -0,0 +1,19 @@
+use crate::engine::renderer::material::Texture;
+
+/// How the OS cursor is constrained to the window.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum CursorGrabMode {
+    /// The cursor can move freely in and out of the window.
+    None,
+    /// The cursor is kept inside the window bounds.
+    Confined,
+    /// The cursor is kept in place, mouse movement is reported as relative motion.
+    Locked
+}
+
+/// A cursor image built from an engine texture.
+#[derive(Debug, Clone)]
+pub struct CustomCursor {
+    pub texture: Texture,
+    pub hotspot: [u32; 2] // pixels, measured from the top left of the image
+}
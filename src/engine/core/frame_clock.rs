@@ -0,0 +1,24 @@
+use std::time::{Duration, Instant};
+
+/// Measures the time that passed between consecutive frames.
+#[derive(Debug, Default)]
+pub struct FrameClock {
+    last_tick: Option<Instant>
+}
+
+impl FrameClock {
+
+    pub fn new() -> Self {
+        Self {
+            last_tick: None
+        }
+    }
+
+    /// Returns the time since the previous tick, or zero on the first tick.
+    pub fn tick(&mut self) -> Duration {
+        let now = Instant::now();
+        let delta_time = self.last_tick.map_or(Duration::ZERO, |last_tick| now - last_tick);
+        self.last_tick = Some(now);
+        delta_time
+    }
+}
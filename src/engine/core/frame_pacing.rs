@@ -0,0 +1,124 @@
+use std::time::{Duration, Instant};
+
+/// When a window renders a new frame.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum UpdateMode {
+    /// Frames are rendered continuously, as games usually do.
+    Continuous,
+    /// Frames are only rendered after input or an explicit redraw request, as editors usually do.
+    Reactive
+}
+
+/// Controls how often frames are rendered, to avoid burning CPU and GPU time when it isn't needed.
+#[derive(Debug, Clone, Copy)]
+pub struct FramePacing {
+    pub mode: UpdateMode,
+    /// Upper bound for the frame rate of a focused window, or `None` to render as fast as possible.
+    pub target_fps: Option<u32>,
+    /// Upper bound for the frame rate of an unfocused or minimised window.
+    pub background_fps: u32
+}
+
+impl Default for FramePacing {
+    fn default() -> Self {
+        Self {
+            mode: UpdateMode::Continuous,
+            target_fps: None,
+            background_fps: 10
+        }
+    }
+}
+
+impl FramePacing {
+
+    /// The minimal time between two frames, zero when the frame rate is unlimited.
+    pub fn frame_interval(&self, focused: bool) -> Duration {
+        let fps = match (focused, self.target_fps) {
+            (true, target_fps) => target_fps,
+            (false, Some(target_fps)) => Some(target_fps.min(self.background_fps)),
+            (false, None) => Some(self.background_fps)
+        };
+
+        fps.map_or(Duration::ZERO, |fps| Duration::from_secs_f64(1.0 / fps.max(1) as f64))
+    }
+
+    /// Determines when the next frame is due.
+    ///
+    /// # Arguments
+    ///
+    /// * `last_frame` - When the previous frame started, `None` if no frame was rendered yet
+    /// * `focused` - Whether the window has focus and is visible
+    /// * `redraw_requested` - Whether input arrived or a redraw was requested since the previous frame
+    /// * `now` - The current time
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Instant>` - When to render the next frame, or `None` to wait for input.
+    pub fn next_frame(&self, last_frame: Option<Instant>, focused: bool, redraw_requested: bool, now: Instant) -> Option<Instant> {
+        if self.mode == UpdateMode::Reactive && !redraw_requested && last_frame.is_some() {
+            return None;
+        }
+
+        let next_frame = last_frame.map_or(now, |last_frame| last_frame + self.frame_interval(focused));
+        Some(next_frame.max(now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+    use crate::engine::core::frame_pacing::{FramePacing, UpdateMode};
+
+    #[test]
+    fn frame_pacing_should_throttle_unfocused_windows_to_background_rate() {
+        // given
+        let pacing = FramePacing {
+            mode: UpdateMode::Continuous,
+            target_fps: Some(50),
+            background_fps: 10
+        };
+
+        // when
+        let focused_interval = pacing.frame_interval(true);
+        let unfocused_interval = pacing.frame_interval(false);
+
+        // then
+        assert_eq!(focused_interval, Duration::from_millis(20));
+        assert_eq!(unfocused_interval, Duration::from_millis(100));
+    }
+
+    #[test]
+    fn frame_pacing_should_schedule_next_frame_after_frame_interval() {
+        // given
+        let pacing = FramePacing {
+            mode: UpdateMode::Continuous,
+            target_fps: Some(50),
+            background_fps: 10
+        };
+        let now = Instant::now();
+
+        // when
+        let next_frame = pacing.next_frame(Some(now), true, false, now);
+
+        // then
+        assert_eq!(next_frame, Some(now + Duration::from_millis(20)));
+    }
+
+    #[test]
+    fn frame_pacing_should_wait_for_input_in_reactive_mode() {
+        // given
+        let pacing = FramePacing {
+            mode: UpdateMode::Reactive,
+            ..FramePacing::default()
+        };
+        let now = Instant::now();
+
+        // when
+        let idle_frame = pacing.next_frame(Some(now), true, false, now);
+        let requested_frame = pacing.next_frame(Some(now), true, true, now);
+
+        // then
+        assert_eq!(idle_frame, None);
+        assert_eq!(requested_frame, Some(now));
+    }
+}
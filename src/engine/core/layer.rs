@@ -1,8 +1,15 @@
+use std::time::Duration;
+use crate::engine::core::window::WindowId;
 use crate::engine::events::Event;
 use crate::engine::renderer::Scene;
 
 pub trait Layer {
-    fn update(&self);
+    /// Called once per frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `delta_time` - The time that passed since the previous frame
+    fn update(&self, delta_time: Duration);
 
     /// Called when an event is triggered.
     ///
@@ -14,6 +21,21 @@ pub trait Layer {
     ///
     /// * `bool` - Whether the event was handled.
     fn handle_event(&self, event: &Event) -> bool;
+
+    /// Called when an event is triggered by the window this layer belongs to.
+    /// Override this instead of `handle_event` when the originating window matters.
+    ///
+    /// # Arguments
+    ///
+    /// * `window_id` - The window the event came from
+    /// * `event` - The event that needs to be handled
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the event was handled.
+    fn handle_window_event(&self, window_id: WindowId, event: &Event) -> bool {
+        self.handle_event(event)
+    }
     
     fn get_name(&self) -> &str;
     
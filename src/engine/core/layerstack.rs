@@ -1,3 +1,4 @@
+use std::fmt::{Debug, Formatter};
 use std::slice::Iter;
 use crate::engine::core::layer::Layer;
 
@@ -6,6 +7,12 @@ pub struct LayerStack {
     overlays: Vec<Box<dyn Layer>>
 }
 
+impl Default for LayerStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl LayerStack {
 
     pub fn new() -> Self {
@@ -31,11 +38,20 @@ impl LayerStack {
         self.overlays.pop();
     }
 
-    pub fn layers(&self) -> Iter<Box<dyn Layer>> {
+    pub fn layers(&self) -> Iter<'_, Box<dyn Layer>> {
         self.layers.iter()
     }
 
-    pub fn overlays(&self) -> Iter<Box<dyn Layer>> {
+    pub fn overlays(&self) -> Iter<'_, Box<dyn Layer>> {
         self.overlays.iter()
     }
-}
\ No newline at end of file
+}
+
+impl Debug for LayerStack {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LayerStack")
+            .field("layers", &self.layers.iter().map(|layer| layer.get_name()).collect::<Vec<_>>())
+            .field("overlays", &self.overlays.iter().map(|overlay| overlay.get_name()).collect::<Vec<_>>())
+            .finish()
+    }
+}
@@ -2,4 +2,8 @@ pub mod mouse_codes;
 pub mod key_codes;
 pub mod window;
 pub mod layer;
-pub mod layerstack;
\ No newline at end of file
+pub mod layerstack;
+pub mod cursor;
+pub mod commands;
+pub mod frame_clock;
+pub mod frame_pacing;
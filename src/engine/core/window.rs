@@ -1,5 +1,15 @@
 use crate::engine::events::Event;
 
+/// Identifies one of the application's windows.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct WindowId(pub u64);
+
+impl WindowId {
+    /// The window that is opened when the application starts.
+    pub const MAIN: WindowId = WindowId(0);
+}
+
+#[derive(Debug, Clone)]
 pub struct WindowProps {
     pub title: String,
     pub width: u32,
@@ -14,4 +24,4 @@ impl Default for WindowProps {
             height: 720
         }
     }
-}
\ No newline at end of file
+}
@@ -22,6 +22,17 @@ pub enum ApplicationEvent {
         width: u32,
         height: u32
     }, 
+    /// The window moved to a monitor with a different DPI, or the DPI setting changed.
+    ScaleFactorChanged {
+        scale_factor: f64
+    },
+    WindowFocused {
+        focused: bool
+    },
+    /// The window became fully hidden, e.g. minimised or covered by other windows, or visible again.
+    WindowOccluded {
+        occluded: bool
+    },
     RenderRequested
 }
 
@@ -33,6 +44,8 @@ pub enum MouseEvent {
     MouseButtonReleased {
         button: MouseCode
     }, 
+    /// Cursor position in physical pixels, or the relative motion since the last event
+    /// while the cursor is locked.
     MouseMoved {
         x: f64,
         y: f64   
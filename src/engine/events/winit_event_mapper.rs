@@ -1,8 +1,8 @@
 use log::debug;
-use winit::event::{ElementState, WindowEvent};
+use winit::event::{DeviceEvent, ElementState, WindowEvent};
 use winit::event::MouseScrollDelta::{LineDelta, PixelDelta};
 use winit::keyboard::PhysicalKey::{Code, Unidentified};
-use crate::engine::events::ApplicationEvent::{RenderRequested, WindowClosed, WindowResized};
+use crate::engine::events::ApplicationEvent::{RenderRequested, ScaleFactorChanged, WindowClosed, WindowFocused, WindowOccluded, WindowResized};
 use crate::engine::events::{ApplicationEvent, Event};
 use crate::engine::events::KeyboardEvent::{KeyPressed, KeyReleased};
 use crate::engine::events::MouseEvent::{MouseButtonPressed, MouseButtonReleased, MouseMoved, MouseScrolled};
@@ -19,6 +19,15 @@ pub fn map_event(event: WindowEvent) -> Option<Event> {
                 height: physical_size.height
             }))
         }
+        WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+            Some(Event::ApplicationEvent(ScaleFactorChanged { scale_factor }))
+        }
+        WindowEvent::Focused(focused) => {
+            Some(Event::ApplicationEvent(WindowFocused { focused }))
+        }
+        WindowEvent::Occluded(occluded) => {
+            Some(Event::ApplicationEvent(WindowOccluded { occluded }))
+        }
         WindowEvent::KeyboardInput { device_id, event, .. } => {
             let key_result = match event.physical_key {
                 Code(key_code) => (key_code as WinitKeyCode).try_into(),
@@ -80,4 +89,14 @@ pub fn map_event(event: WindowEvent) -> Option<Event> {
             None
         }
     }
-}
\ No newline at end of file
+}
+
+/// Maps raw device input, only used while the cursor is locked to the window.
+pub fn map_device_event(event: DeviceEvent) -> Option<Event> {
+    match event {
+        DeviceEvent::MouseMotion { delta: (x, y) } => {
+            Some(Event::MouseEvent(MouseMoved { x, y }))
+        }
+        _ => None
+    }
+}
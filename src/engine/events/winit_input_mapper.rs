@@ -1,8 +1,14 @@
 use winit::event::MouseButton;
+use winit::window::CustomCursorSource;
+use crate::engine::core::cursor::{CursorGrabMode, CustomCursor};
+use crate::engine::renderer::material::Texture;
 use crate::engine::core::key_codes::KeyCode;
 use crate::engine::core::mouse_codes::MouseCode;
 
 pub type WinitKeyCode = winit::keyboard::KeyCode;
+pub type WinitCursorGrabMode = winit::window::CursorGrabMode;
+pub type WinitCustomCursor = winit::window::CustomCursor;
+pub type WinitWindowId = winit::window::WindowId;
 
 /// conversion between the engine's KeyCode and Winit's KeyCode.
 impl TryInto<KeyCode> for WinitKeyCode {
@@ -146,4 +152,107 @@ impl TryInto<MouseCode> for MouseButton {
             _ => Err("Unmapped mouse button")
         }
     }
-}
\ No newline at end of file
+}
+
+impl From<CursorGrabMode> for WinitCursorGrabMode {
+    fn from(mode: CursorGrabMode) -> Self {
+        match mode {
+            CursorGrabMode::None => WinitCursorGrabMode::None,
+            CursorGrabMode::Confined => WinitCursorGrabMode::Confined,
+            CursorGrabMode::Locked => WinitCursorGrabMode::Locked
+        }
+    }
+}
+
+/// conversion between the engine's CustomCursor and a Winit cursor source.
+impl TryFrom<&CustomCursor> for CustomCursorSource {
+    type Error = String;
+
+    fn try_from(cursor: &CustomCursor) -> Result<Self, Self::Error> {
+        let texture = &cursor.texture;
+        let width: u16 = texture.width.try_into()
+            .map_err(|_| format!("cursor texture {} is too wide", texture.name))?;
+        let height: u16 = texture.height.try_into()
+            .map_err(|_| format!("cursor texture {} is too high", texture.name))?;
+        let rgba = cursor_rgba(texture)?;
+        let [hotspot_x, hotspot_y] = cursor_hotspot(cursor);
+
+        WinitCustomCursor::from_rgba(rgba, width, height, hotspot_x, hotspot_y)
+            .map_err(|error| format!("invalid cursor texture {}: {}", texture.name, error))
+    }
+}
+
+/// The pixels of a cursor texture with the top row first.
+fn cursor_rgba(texture: &Texture) -> Result<Vec<u8>, String> {
+    if texture.width == 0 || texture.height == 0 {
+        return Err(format!("cursor texture {} has no pixels", texture.name));
+    }
+    let row_bytes = texture.width as usize * 4;
+    let expected_bytes = row_bytes * texture.height as usize;
+    if texture.data.len() != expected_bytes {
+        return Err(format!("cursor texture {} has {} bytes instead of {}", texture.name, texture.data.len(), expected_bytes));
+    }
+
+    // textures are stored with their 0,0 coordinate at the bottom left, cursors expect it at the top left
+    Ok(texture.data
+        .chunks_exact(row_bytes)
+        .rev()
+        .flatten()
+        .copied()
+        .collect())
+}
+
+/// The hotspot of a cursor, moved onto the image if it lies outside of it.
+fn cursor_hotspot(cursor: &CustomCursor) -> [u16; 2] {
+    let texture = &cursor.texture;
+    [
+        cursor.hotspot[0].min(texture.width.saturating_sub(1)) as u16,
+        cursor.hotspot[1].min(texture.height.saturating_sub(1)) as u16
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::core::cursor::CustomCursor;
+    use crate::engine::events::winit_input_mapper::{cursor_hotspot, cursor_rgba};
+    use crate::engine::renderer::material::Texture;
+
+    #[test]
+    fn cursor_rgba_should_reject_empty_textures() {
+        // given
+        let texture = Texture::from_pixels(String::from("cursor"), 0, 4, Vec::new());
+
+        // then
+        assert!(cursor_rgba(&texture).is_err());
+    }
+
+    #[test]
+    fn cursor_rgba_should_reject_the_wrong_amount_of_data() {
+        // given: a 2x2 texture missing its top row
+        let texture = Texture::from_pixels(String::from("cursor"), 2, 2, vec![0; 2 * 4]);
+
+        // then
+        assert!(cursor_rgba(&texture).is_err());
+    }
+
+    #[test]
+    fn cursor_rgba_should_put_the_top_row_first() {
+        // given: a 1x2 texture stored bottom row first
+        let texture = Texture::from_pixels(String::from("cursor"), 1, 2, vec![1, 1, 1, 255, 2, 2, 2, 255]);
+
+        // then
+        assert_eq!(cursor_rgba(&texture).unwrap(), vec![2, 2, 2, 255, 1, 1, 1, 255]);
+    }
+
+    #[test]
+    fn cursor_hotspot_should_be_clamped_to_the_image() {
+        // given
+        let cursor = CustomCursor {
+            texture: Texture::filled("cursor", 16, 8, 0),
+            hotspot: [3, 20]
+        };
+
+        // then
+        assert_eq!(cursor_hotspot(&cursor), [3, 7]);
+    }
+}
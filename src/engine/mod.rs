@@ -1,4 +1,6 @@
 pub mod application;
 pub mod core;
 pub mod events;
-pub mod renderer;
\ No newline at end of file
+pub mod renderer;
+pub mod runners;
+pub mod resources;
@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use crate::engine::renderer::texture_region::TextureRegion;
+
+/// What a clip does when it reaches its last frame.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PlaybackMode {
+    /// Starts over at the first frame.
+    Loop,
+    /// Stops at the last frame.
+    Once,
+    /// Plays backwards to the first frame, then forwards again.
+    PingPong
+}
+
+#[derive(Debug, Clone)]
+pub struct AnimationFrame {
+    pub region: TextureRegion,
+    pub duration: Duration
+}
+
+/// A sequence of frames, e.g. a walk cycle from a sprite sheet.
+#[derive(Debug, Clone)]
+pub struct AnimationClip {
+    pub name: String,
+    pub frames: Vec<AnimationFrame>,
+    pub mode: PlaybackMode
+}
+
+impl AnimationClip {
+
+    /// A clip that shows every region for the same duration.
+    pub fn from_regions(name: String, regions: &[TextureRegion], frame_duration: Duration, mode: PlaybackMode) -> Self {
+        Self {
+            name,
+            frames: regions.iter()
+                .map(|region| AnimationFrame { region: *region, duration: frame_duration })
+                .collect(),
+            mode
+        }
+    }
+
+    fn total_duration(&self) -> Duration {
+        self.frames.iter().map(|frame| frame.duration).sum()
+    }
+}
+
+/// Something that happened while the animator advanced, e.g. to play a footstep sound on a certain frame.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum AnimationEvent {
+    /// The clip moved on to another frame.
+    FrameChanged { clip: String, frame: usize },
+    /// The clip played to its end: every cycle for looping and ping-pong clips, once for the others.
+    ClipEnded { clip: String }
+}
+
+/// Plays one of its clips at a time, advanced by the frame's delta time.
+#[derive(Debug, Clone)]
+pub struct Animator {
+    pub speed: f32, // 1.0 is normal speed, 2.0 twice as fast
+    clips: HashMap<String, AnimationClip>,
+    current_clip: Option<String>,
+    playback: Playback
+}
+
+/// Where the animator is within the current clip, kept apart from the clips so that
+/// it can advance while the clip is borrowed.
+#[derive(Debug, Clone, Default)]
+struct Playback {
+    frame: usize,
+    elapsed: Duration, // time spent on the current frame
+    backwards: bool, // ping-pong direction
+    finished: bool
+}
+
+impl Default for Animator {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            clips: HashMap::new(),
+            current_clip: None,
+            playback: Playback::default()
+        }
+    }
+}
+
+impl Animator {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a clip, or replaces the one of the same name, e.g. when a sprite sheet is reloaded.
+    /// A replaced clip that is playing starts over from its first frame.
+    pub fn add_clip(&mut self, clip: AnimationClip) {
+        if self.current_clip.as_deref() == Some(clip.name.as_str()) {
+            self.restart();
+        }
+        self.clips.insert(clip.name.clone(), clip);
+    }
+
+    /// Starts playing a clip from its first frame, unless it is already playing.
+    pub fn play(&mut self, clip_name: &str) -> Result<(), String> {
+        if !self.clips.contains_key(clip_name) {
+            return Err(format!("Animation clip {} not found", clip_name));
+        }
+        if self.current_clip.as_deref() != Some(clip_name) {
+            self.current_clip = Some(String::from(clip_name));
+            self.restart();
+        }
+        Ok(())
+    }
+
+    /// Plays the current clip again from its first frame.
+    pub fn restart(&mut self) {
+        self.playback = Playback::default();
+    }
+
+    pub fn current_clip(&self) -> Option<&str> {
+        self.current_clip.as_deref()
+    }
+
+    /// The index of the frame within the current clip.
+    pub fn current_frame(&self) -> usize {
+        self.playback.frame
+    }
+
+    /// The texture region to draw for the current frame.
+    pub fn current_region(&self) -> Option<TextureRegion> {
+        self.clip().and_then(|clip| clip.frames.get(self.playback.frame)).map(|frame| frame.region)
+    }
+
+    /// Whether a clip that plays once has reached its end.
+    pub fn is_finished(&self) -> bool {
+        self.playback.finished
+    }
+
+    /// Advances the current clip, possibly by several frames when the delta time is long.
+    pub fn update(&mut self, delta_time: Duration) -> Vec<AnimationEvent> {
+        let mut events = Vec::new();
+        let Some(clip) = self.current_clip.as_ref().and_then(|name| self.clips.get(name)) else {
+            return events;
+        };
+        let playback = &mut self.playback;
+        // without duration, the clip would advance forever
+        if playback.finished || self.speed <= 0.0 || clip.total_duration().is_zero() {
+            return events;
+        }
+
+        playback.elapsed += delta_time.mul_f32(self.speed);
+        while playback.elapsed >= clip.frames[playback.frame].duration {
+            playback.elapsed -= clip.frames[playback.frame].duration;
+            let (next_frame, clip_ended) = playback.next_frame(clip);
+
+            if next_frame != playback.frame {
+                playback.frame = next_frame;
+                events.push(AnimationEvent::FrameChanged { clip: clip.name.clone(), frame: next_frame });
+            }
+            if clip_ended {
+                events.push(AnimationEvent::ClipEnded { clip: clip.name.clone() });
+            }
+            if playback.finished {
+                playback.elapsed = Duration::ZERO;
+                break;
+            }
+        }
+        events
+    }
+
+    fn clip(&self) -> Option<&AnimationClip> {
+        self.current_clip.as_ref().and_then(|name| self.clips.get(name))
+    }
+}
+
+impl Playback {
+
+    /// The frame after the current one, and whether the clip ended with it.
+    fn next_frame(&mut self, clip: &AnimationClip) -> (usize, bool) {
+        let last_frame = clip.frames.len() - 1;
+        match clip.mode {
+            PlaybackMode::Loop if self.frame == last_frame => (0, true),
+            PlaybackMode::Loop => (self.frame + 1, false),
+            PlaybackMode::Once if self.frame == last_frame => {
+                self.finished = true;
+                (self.frame, true)
+            },
+            PlaybackMode::Once => (self.frame + 1, false),
+            PlaybackMode::PingPong if last_frame == 0 => (0, true),
+            PlaybackMode::PingPong if self.backwards && self.frame == 1 => {
+                self.backwards = false;
+                (0, true)
+            },
+            PlaybackMode::PingPong if self.backwards => (self.frame - 1, false),
+            PlaybackMode::PingPong if self.frame == last_frame => {
+                self.backwards = true;
+                (self.frame - 1, false)
+            },
+            PlaybackMode::PingPong => (self.frame + 1, false)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use crate::engine::renderer::animation::{AnimationClip, AnimationEvent, Animator, PlaybackMode};
+    use crate::engine::renderer::texture_region::TextureRegion;
+
+    fn animator_with_clip(mode: PlaybackMode, frames: u32) -> Animator {
+        let regions: Vec<TextureRegion> = (0..frames)
+            .map(|frame| TextureRegion { x: frame * 16, y: 0, width: 16, height: 16, texture_size: [16 * frames, 16] })
+            .collect();
+        let mut animator = Animator::new();
+        animator.add_clip(AnimationClip::from_regions(String::from("walk"), &regions, Duration::from_millis(100), mode));
+        animator.play("walk").unwrap();
+        animator
+    }
+
+    fn frames_over_time(animator: &mut Animator, steps: usize) -> Vec<usize> {
+        (0..steps).map(|_| {
+            animator.update(Duration::from_millis(100));
+            animator.current_frame()
+        }).collect()
+    }
+
+    #[test]
+    fn animator_should_start_over_in_loop_mode() {
+        // given
+        let mut animator = animator_with_clip(PlaybackMode::Loop, 3);
+
+        // when
+        let frames = frames_over_time(&mut animator, 4);
+
+        // then
+        assert_eq!(frames, vec![1, 2, 0, 1]);
+    }
+
+    #[test]
+    fn animator_should_stop_at_last_frame_in_once_mode() {
+        // given
+        let mut animator = animator_with_clip(PlaybackMode::Once, 3);
+
+        // when
+        let frames = frames_over_time(&mut animator, 4);
+
+        // then
+        assert_eq!(frames, vec![1, 2, 2, 2]);
+        assert!(animator.is_finished());
+        assert_eq!(animator.current_region().unwrap().x, 32);
+    }
+
+    #[test]
+    fn animator_should_reverse_at_the_ends_in_ping_pong_mode() {
+        // given
+        let mut animator = animator_with_clip(PlaybackMode::PingPong, 3);
+
+        // when
+        let frames = frames_over_time(&mut animator, 6);
+
+        // then
+        assert_eq!(frames, vec![1, 2, 1, 0, 1, 2]);
+    }
+
+    #[test]
+    fn animator_should_fire_frame_and_clip_end_events() {
+        // given: twice the speed, so 250ms covers 5 frames
+        let mut animator = animator_with_clip(PlaybackMode::Loop, 2);
+        animator.speed = 2.0;
+
+        // when
+        let events = animator.update(Duration::from_millis(250));
+
+        // then
+        let walk = String::from("walk");
+        assert_eq!(events, vec![
+            AnimationEvent::FrameChanged { clip: walk.clone(), frame: 1 },
+            AnimationEvent::FrameChanged { clip: walk.clone(), frame: 0 },
+            AnimationEvent::ClipEnded { clip: walk.clone() },
+            AnimationEvent::FrameChanged { clip: walk.clone(), frame: 1 },
+            AnimationEvent::FrameChanged { clip: walk.clone(), frame: 0 },
+            AnimationEvent::ClipEnded { clip: walk.clone() },
+            AnimationEvent::FrameChanged { clip: walk.clone(), frame: 1 }
+        ]);
+        assert_eq!(animator.current_frame(), 1);
+    }
+
+    #[test]
+    fn animator_should_fail_to_play_unknown_clip() {
+        // given
+        let mut animator = animator_with_clip(PlaybackMode::Loop, 2);
+
+        // when
+        let result = animator.play("attack");
+
+        // then
+        assert!(result.is_err());
+        assert_eq!(animator.current_clip(), Some("walk"));
+    }
+
+    #[test]
+    fn animator_should_restart_a_playing_clip_that_is_replaced() {
+        // given: the last of four frames is showing
+        let mut animator = animator_with_clip(PlaybackMode::Loop, 4);
+        frames_over_time(&mut animator, 3);
+        assert_eq!(animator.current_frame(), 3);
+
+        // when: the clip is replaced by one with two frames
+        let regions: Vec<TextureRegion> = (0..2)
+            .map(|frame| TextureRegion { x: frame * 16, y: 0, width: 16, height: 16, texture_size: [32, 16] })
+            .collect();
+        animator.add_clip(AnimationClip::from_regions(String::from("walk"), &regions, Duration::from_millis(100), PlaybackMode::Loop));
+        let frames = frames_over_time(&mut animator, 3);
+
+        // then
+        assert_eq!(frames, vec![1, 0, 1]);
+    }
+}
@@ -0,0 +1,92 @@
+use glam::{Mat4, Vec4};
+
+/// An axis aligned bounding box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: [f32; 2], // lower left corner
+    pub max: [f32; 2] // upper right corner
+}
+
+impl Aabb {
+
+    /// The smallest box around all points, `None` when there are no points.
+    pub fn from_points(points: impl IntoIterator<Item = [f32; 2]>) -> Option<Aabb> {
+        points.into_iter().fold(None, |bounds: Option<Aabb>, [x, y]| match bounds {
+            Some(bounds) => Some(Aabb {
+                min: [bounds.min[0].min(x), bounds.min[1].min(y)],
+                max: [bounds.max[0].max(x), bounds.max[1].max(y)]
+            }),
+            None => Some(Aabb { min: [x, y], max: [x, y] })
+        })
+    }
+
+    pub fn corners(&self) -> [[f32; 2]; 4] {
+        [
+            self.min,
+            [self.max[0], self.min[1]],
+            self.max,
+            [self.min[0], self.max[1]]
+        ]
+    }
+
+    /// The box around this box after transforming it, e.g. from local to world space.
+    /// Rotations make the result larger than the transformed box itself.
+    pub fn transformed(&self, matrix: Mat4) -> Aabb {
+        let corners = self.corners().map(|[x, y]| {
+            let corner = matrix * Vec4::new(x, y, 0.0, 1.0);
+            [corner.x, corner.y]
+        });
+        Aabb::from_points(corners).unwrap()
+    }
+
+    /// Whether the boxes overlap, boxes that only touch at an edge count as overlapping.
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min[0] <= other.max[0] && other.min[0] <= self.max[0]
+            && self.min[1] <= other.max[1] && other.min[1] <= self.max[1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_4;
+    use glam::{Mat4, Vec2, Vec3};
+    use crate::engine::renderer::bounds::Aabb;
+
+    #[test]
+    fn aabb_should_enclose_all_points() {
+        // when
+        let bounds = Aabb::from_points([[1.0, -2.0], [-3.0, 4.0], [0.0, 0.0]]);
+        let no_bounds = Aabb::from_points([]);
+
+        // then
+        assert_eq!(bounds, Some(Aabb { min: [-3.0, -2.0], max: [1.0, 4.0] }));
+        assert_eq!(no_bounds, None);
+    }
+
+    #[test]
+    fn aabb_should_grow_when_transformed_with_rotation() {
+        // given
+        let bounds = Aabb { min: [-0.5, -0.5], max: [0.5, 0.5] };
+        let matrix = Mat4::from_translation(Vec3::new(10.0, 0.0, 0.0)) * Mat4::from_rotation_z(FRAC_PI_4);
+
+        // when
+        let transformed = bounds.transformed(matrix);
+
+        // then: the diagonal of the unit square is the new width and height
+        let half_diagonal = 0.5_f32.hypot(0.5);
+        assert!(Vec2::from(transformed.min).abs_diff_eq(Vec2::new(10.0 - half_diagonal, -half_diagonal), 1e-5));
+        assert!(Vec2::from(transformed.max).abs_diff_eq(Vec2::new(10.0 + half_diagonal, half_diagonal), 1e-5));
+    }
+
+    #[test]
+    fn aabb_should_intersect_only_overlapping_boxes() {
+        // given
+        let bounds = Aabb { min: [0.0, 0.0], max: [10.0, 10.0] };
+
+        // then
+        assert!(bounds.intersects(&Aabb { min: [9.0, 9.0], max: [11.0, 11.0] }));
+        assert!(bounds.intersects(&Aabb { min: [10.0, 0.0], max: [11.0, 1.0] }));
+        assert!(!bounds.intersects(&Aabb { min: [11.0, 0.0], max: [12.0, 1.0] }));
+        assert!(!bounds.intersects(&Aabb { min: [0.0, -2.0], max: [1.0, -1.0] }));
+    }
+}
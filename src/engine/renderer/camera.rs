@@ -1,44 +1,338 @@
-use glam::{Mat4, Vec3};
+use std::rc::Rc;
+use glam::{Mat4, Vec2, Vec3, Vec4};
+use crate::engine::renderer::bounds::Aabb;
+use crate::engine::renderer::material::Texture;
 
+/// How the camera's size is mapped onto a viewport whose aspect ratio might differ.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScalingMode {
+    /// Shows exactly the camera's height, the visible width follows from the viewport's aspect ratio.
+    FitHeight,
+    /// Shows exactly the camera's size as a fixed virtual resolution,
+    /// with bars on the sides (pillarbox) or at the top and bottom (letterbox).
+    Letterbox,
+    /// Shows at least the camera's size, and more of the world along the longer axis.
+    Expand,
+    /// Shows exactly the camera's size, stretched to fill the viewport.
+    Stretch,
+    /// Renders the camera's size at a low resolution, upscaled with nearest filtering by the
+    /// largest integer factor that fits, with bars around it. Keeps pixel art sharp and even.
+    /// A viewport smaller than the resolution shows it scaled down to fit, no longer pixel perfect.
+    PixelPerfect {
+        resolution: [u32; 2] // pixels
+    },
+    /// Ignores the camera's size and shows one world unit per screen unit, with the origin at the lower left
+    /// of the viewport. Meant for UI overlays that should not move with the game camera.
+    ScreenSpace {
+        units: ScreenUnits
+    }
+}
+
+/// The unit of a screen space camera.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ScreenUnits {
+    /// Physical pixels, UI appears smaller on high DPI screens.
+    Physical,
+    /// DPI independent logical pixels, UI has the same apparent size on every screen.
+    Logical
+}
+
+/// The point of the view an element is attached to. The element's matching corner, or its center,
+/// stays at that point when the view changes size.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Anchor {
+    TopLeft,
+    Center,
+    BottomRight
+}
+
+/// A rectangle in physical pixels, with the origin at the top left.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct PixelRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32
+}
+
+/// A part of the render surface in fractions of its size, from 0 to 1, with the origin at the top left.
+/// Cameras render to their own part, e.g. one half of the surface each for split-screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewportRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32
+}
+
+impl ViewportRect {
+    pub const FULL: ViewportRect = ViewportRect { x: 0.0, y: 0.0, width: 1.0, height: 1.0 };
+
+    /// The rectangle in physical pixels on a surface of the given size. Edges are rounded the same way
+    /// for neighbouring rectangles, so that they tile without gaps or overlap.
+    pub fn to_pixels(&self, surface_size: [u32; 2]) -> PixelRect {
+        let [surface_width, surface_height] = surface_size.map(|size| size as f32);
+        let left = (self.x * surface_width).round().max(0.0) as u32;
+        let top = (self.y * surface_height).round().max(0.0) as u32;
+        let right = ((self.x + self.width) * surface_width).round().max(0.0) as u32;
+        let bottom = ((self.y + self.height) * surface_height).round().max(0.0) as u32;
+        PixelRect {
+            x: left,
+            y: top,
+            width: right.saturating_sub(left),
+            height: bottom.saturating_sub(top)
+        }
+    }
+}
+
+/// What happens to a camera's part of the surface before the camera draws on it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClearPolicy {
+    /// Draws on top of what is already there, e.g. the cameras rendered before it.
+    Keep,
+    /// Fills the camera's part of the surface with a linear RGBA colour first.
+    Color([f32; 4])
+}
+
+#[derive(Debug, Clone)]
 pub struct Camera2D {
-    pub position: [f32; 2], // center position in world units
-    pub size: [f32; 2], // world units
-    pub viewport_size: [u32; 2] // screen pixels
+    pub position: [f32; 2], // lower left position in world units
+    pub size: [f32; 2], // world units, how much of it is visible depends on the scaling mode
+    pub viewport_size: [u32; 2], // screen pixels, the size of the whole render surface
+    pub viewport: ViewportRect, // the part of the render surface this camera renders to
+    pub clear: ClearPolicy,
+    pub zoom: f32, // magnification around the center of the view, 2.0 shows everything twice as large
+    pub rotation: f32, // radians, counter-clockwise around the center of the view
+    pub scale_factor: f64, // physical pixels per logical pixel, from the window's DPI
+    pub scaling_mode: ScalingMode,
+    pub offset: [f32; 2], // world units, a temporary displacement on top of the position, e.g. for screen shake
+    pub offset_rotation: f32, // radians, a temporary rotation on top of the rotation
+    pub target: Option<Rc<Texture>> // a render target texture to draw to instead of the surface, which then sets the viewport size
+}
+
+impl Default for Camera2D {
+    fn default() -> Self {
+        Self {
+            position: [0.0, 0.0],
+            size: [1.0, 1.0],
+            viewport_size: [1, 1],
+            viewport: ViewportRect::FULL,
+            clear: ClearPolicy::Keep,
+            zoom: 1.0,
+            rotation: 0.0,
+            scale_factor: 1.0,
+            scaling_mode: ScalingMode::FitHeight,
+            offset: [0.0, 0.0],
+            offset_rotation: 0.0,
+            target: None
+        }
+    }
 }
 
 impl Camera2D {
-    
+
+    /// A camera for UI overlays, covering the whole surface in the given screen units.
+    pub fn screen_space(units: ScreenUnits) -> Self {
+        Self {
+            scaling_mode: ScalingMode::ScreenSpace { units },
+            ..Camera2D::default()
+        }
+    }
+
     pub fn update_viewport_size(&mut self, new_size: [u32; 2]) {
         self.viewport_size = new_size;
     }
-    
+
+    pub fn update_scale_factor(&mut self, scale_factor: f64) {
+        self.scale_factor = scale_factor;
+    }
+
+    /// The camera's part of the render surface in physical pixels, including the bars of the
+    /// letterbox and pixel perfect modes.
+    pub fn region(&self) -> PixelRect {
+        self.viewport.to_pixels(self.viewport_size)
+    }
+
+    pub fn aspect_ratio(&self) -> f32 {
+        let region = self.region();
+        if region.height == 0 {
+            return 1.0;
+        }
+        region.width as f32 / region.height as f32
+    }
+
+    /// The width and height of the view in world units, before zoom and rotation are applied.
+    pub fn view_size(&self) -> [f32; 2] {
+        let [width, height] = self.size;
+        match self.scaling_mode {
+            ScalingMode::FitHeight => [height * self.aspect_ratio(), height],
+            ScalingMode::Expand if self.aspect_ratio() > width / height => [height * self.aspect_ratio(), height],
+            ScalingMode::Expand => [width, width / self.aspect_ratio()],
+            ScalingMode::Letterbox | ScalingMode::Stretch | ScalingMode::PixelPerfect { .. } => self.size,
+            ScalingMode::ScreenSpace { units } => {
+                let region = self.region();
+                let pixels_per_unit = match units {
+                    ScreenUnits::Physical => 1.0,
+                    ScreenUnits::Logical => self.scale_factor as f32
+                };
+                [region.width as f32 / pixels_per_unit, region.height as f32 / pixels_per_unit]
+            }
+        }
+    }
+
+    /// The part of the region the camera renders to, in physical pixels. Outside of it are the bars
+    /// of the letterbox and pixel perfect modes.
+    pub fn pixel_viewport(&self) -> PixelRect {
+        let region = self.region();
+        let (viewport_width, viewport_height) = (region.width, region.height);
+        let [width, height] = match self.scaling_mode {
+            ScalingMode::FitHeight | ScalingMode::Expand | ScalingMode::Stretch | ScalingMode::ScreenSpace { .. } => [viewport_width, viewport_height],
+            // without an area to keep the aspect ratio of, the whole viewport is used
+            ScalingMode::Letterbox if !self.size.iter().all(|size| *size > 0.0) => [viewport_width, viewport_height],
+            ScalingMode::Letterbox => {
+                let scale = (viewport_width as f32 / self.size[0]).min(viewport_height as f32 / self.size[1]);
+                [(self.size[0] * scale).round() as u32, (self.size[1] * scale).round() as u32]
+            },
+            ScalingMode::PixelPerfect { resolution } => {
+                let [resolution_width, resolution_height] = resolution.map(|pixels| pixels.max(1));
+                let scale = (viewport_width / resolution_width).min(viewport_height / resolution_height);
+                if scale >= 1 {
+                    [resolution_width * scale, resolution_height * scale]
+                } else {
+                    // too small for a single copy of the resolution
+                    let scale = (viewport_width as f32 / resolution_width as f32).min(viewport_height as f32 / resolution_height as f32);
+                    [(resolution_width as f32 * scale).round() as u32, (resolution_height as f32 * scale).round() as u32]
+                }
+            }
+        };
+
+        PixelRect {
+            x: region.x + viewport_width.saturating_sub(width) / 2,
+            y: region.y + viewport_height.saturating_sub(height) / 2,
+            width,
+            height
+        }
+    }
+
+    /// The world position at the center of the view, excluding the offset.
+    pub fn center(&self) -> [f32; 2] {
+        let [width, height] = self.view_size();
+        [self.position[0] + width / 2.0, self.position[1] + height / 2.0]
+    }
+
+    pub fn set_center(&mut self, center: [f32; 2]) {
+        let [width, height] = self.view_size();
+        self.position = [center[0] - width / 2.0, center[1] - height / 2.0];
+    }
+
+    /// The width and height of the visible part of the world, taking zoom into account.
+    pub fn visible_size(&self) -> [f32; 2] {
+        let [width, height] = self.view_size();
+        [width / self.zoom, height / self.zoom]
+    }
+
+    /// The part of the world that is visible, including zoom, rotation and offset.
+    /// With rotation, the bounds are larger than what is actually visible.
+    pub fn visible_bounds(&self) -> Aabb {
+        let [width, height] = self.view_size();
+        Aabb { min: [0.0, 0.0], max: [width, height] }.transformed(self.world_to_view_matrix().inverse())
+    }
+
+    /// The lower left position of an element of the given size, placed at an anchor of the view.
+    /// The margin moves it inwards from the anchor, or for the center anchor, right and up.
+    /// Zoom and rotation are not taken into account.
+    pub fn anchored_position(&self, anchor: Anchor, element_size: [f32; 2], margin: [f32; 2]) -> [f32; 2] {
+        let [width, height] = self.view_size();
+        let [x, y] = match anchor {
+            Anchor::TopLeft => [margin[0], height - element_size[1] - margin[1]],
+            Anchor::Center => [(width - element_size[0]) / 2.0 + margin[0], (height - element_size[1]) / 2.0 + margin[1]],
+            Anchor::BottomRight => [width - element_size[0] - margin[0], margin[1]]
+        };
+        [self.position[0] + x, self.position[1] + y]
+    }
+
     pub fn world_to_view_matrix(&self) -> Mat4 {
         // from world space to screen space
         // translate world coordinates so that the camera position becomes the origin (0, 0) in view space
-        Mat4::from_translation(Vec3::new(-self.position[0], -self.position[1], 0.0))
+        let translation = Mat4::from_translation(Vec3::new(
+            -(self.position[0] + self.offset[0]),
+            -(self.position[1] + self.offset[1]),
+            0.0
+        ));
+
+        // zoom and rotate around the center of the view, the inverse rotation turns the world instead of the camera
+        let [width, height] = self.view_size();
+        let center = Vec3::new(width / 2.0, height / 2.0, 0.0);
+        let zoom_and_rotation = Mat4::from_translation(center)
+            * Mat4::from_scale(Vec3::new(self.zoom, self.zoom, 1.0))
+            * Mat4::from_rotation_z(-(self.rotation + self.offset_rotation))
+            * Mat4::from_translation(-center);
+
+        // executed right to left
+        zoom_and_rotation * translation
     }
-    
+
     pub fn view_to_clip_matrix(&self) -> Mat4 {
-        let aspect_ratio = self.viewport_size[0] / self.viewport_size[1];
-        
-        let height = self.size[1];
-        let width = height * (aspect_ratio as f32);
-        
+        let [width, height] = self.view_size();
+
         let left = 0.0;
         let right = width;
-        let bottom = 0.0;
-        let top = height;
+        // render targets are stored bottom row first like all textures, upside down compared to the surface
+        let (bottom, top) = if self.target.is_some() { (height, 0.0) } else { (0.0, height) };
         let near = 0.0;
         let far = 100.0;
-        
+
         Mat4::orthographic_lh(left, right, bottom, top, near, far)
     }
+
+    pub fn world_to_clip_matrix(&self) -> Mat4 {
+        self.view_to_clip_matrix() * self.world_to_view_matrix()
+    }
+
+    /// Converts a position in physical pixels, with the origin at the top left of the viewport
+    /// (as reported by mouse events), to world units.
+    pub fn screen_to_world(&self, screen_position: [f32; 2]) -> [f32; 2] {
+        let viewport = self.pixel_viewport();
+        let clip_position = Vec4::new(
+            2.0 * (screen_position[0] - viewport.x as f32) / viewport.width.max(1) as f32 - 1.0,
+            1.0 - 2.0 * (screen_position[1] - viewport.y as f32) / viewport.height.max(1) as f32,
+            0.0,
+            1.0
+        );
+        let world_position = self.world_to_clip_matrix().inverse() * clip_position;
+        [world_position.x, world_position.y]
+    }
+
+    /// Converts a position in world units to physical pixels, with the origin at the top left of the viewport.
+    pub fn world_to_screen(&self, world_position: [f32; 2]) -> [f32; 2] {
+        let viewport = self.pixel_viewport();
+        let clip_position = self.world_to_clip_matrix() * Vec4::new(world_position[0], world_position[1], 0.0, 1.0);
+        [
+            viewport.x as f32 + (clip_position.x + 1.0) / 2.0 * viewport.width as f32,
+            viewport.y as f32 + (1.0 - clip_position.y) / 2.0 * viewport.height as f32
+        ]
+    }
+
+    /// Like `screen_to_world`, for a position in DPI independent logical pixels.
+    pub fn logical_to_world(&self, logical_position: [f32; 2]) -> [f32; 2] {
+        let scale_factor = self.scale_factor as f32;
+        self.screen_to_world((Vec2::from(logical_position) * scale_factor).into())
+    }
+
+    /// Like `world_to_screen`, for a position in DPI independent logical pixels.
+    pub fn world_to_logical(&self, world_position: [f32; 2]) -> [f32; 2] {
+        let scale_factor = self.scale_factor as f32;
+        (Vec2::from(self.world_to_screen(world_position)) / scale_factor).into()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use glam::{Mat4, Vec4};
-    use crate::engine::renderer::camera::Camera2D;
+    use std::f32::consts::FRAC_PI_2;
+    use std::rc::Rc;
+    use glam::{Mat4, Vec2, Vec4};
+    use crate::engine::renderer::camera::{Anchor, Camera2D, PixelRect, ScalingMode, ScreenUnits, ViewportRect};
+    use crate::engine::renderer::material::Texture;
 
     #[test]
     fn camera2d_should_translate_world_coordinates_to_view_space() {
@@ -46,32 +340,34 @@ mod tests {
         let camera = Camera2D {
             position: [8.0, 8.0],
             size: [10.0, 10.0],
-            viewport_size: [10, 10]
+            viewport_size: [10, 10],
+            ..Camera2D::default()
         };
         let world_position = Vec4::new(10.0, 10.0, 0.0, 1.0);
-        
+
         // when
         let world_to_view_matrix: Mat4 = camera.world_to_view_matrix();
         let view_position: Vec4 = world_to_view_matrix * world_position;
-        
+
         // then: position has shifted 8 positions across the x/y-axis to the lower/bottom left of the camera
         assert_eq!(view_position, Vec4::new(2.0, 2.0, 0.0, 1.0));
     }
-    
+
     #[test]
     fn camera2d_should_translate_view_coordinates_to_clip_space_when_position_inside_camera_bounds() {
         // given
         let camera = Camera2D {
             position: [0.0, 0.0],
             size: [10.0, 10.0],
-            viewport_size: [10, 10] // the same as size to keep a 1:1 aspect ratio
+            viewport_size: [10, 10], // the same as size to keep a 1:1 aspect ratio
+            ..Camera2D::default()
         };
         let view_position = Vec4::new(5.0, 5.0, 0.0, 1.0);
-        
+
         // when
         let view_to_clip_matrix = camera.view_to_clip_matrix();
         let clip_position = view_to_clip_matrix * view_position;
-        
+
         // then
         assert_eq!(clip_position, Vec4::new(0.0, 0.0, 0.0, 1.0));
     }
@@ -82,7 +378,8 @@ mod tests {
         let camera = Camera2D {
             position: [0.0, 0.0],
             size: [10.0, 10.0],
-            viewport_size: [10, 10] // the same as size to keep a 1:1 aspect ratio
+            viewport_size: [10, 10], // the same as size to keep a 1:1 aspect ratio
+            ..Camera2D::default()
         };
         let view_position = Vec4::new(15.0, 15.0, 0.0, 1.0);
 
@@ -93,4 +390,276 @@ mod tests {
         // then
         assert_eq!(clip_position, Vec4::new(2.0, 2.0, 0.0, 1.0));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn camera2d_should_use_fractional_aspect_ratio() {
+        // given
+        let camera = Camera2D {
+            size: [9.0, 9.0],
+            viewport_size: [1280, 720],
+            ..Camera2D::default()
+        };
+
+        // when
+        let view_size = camera.view_size();
+
+        // then: 16:9, not 1:1 from integer division
+        assert_eq!(view_size, [16.0, 9.0]);
+    }
+
+    #[test]
+    fn camera2d_should_zoom_and_rotate_around_view_center() {
+        // given
+        let camera = Camera2D {
+            size: [10.0, 10.0],
+            viewport_size: [10, 10],
+            zoom: 2.0,
+            rotation: FRAC_PI_2,
+            ..Camera2D::default()
+        };
+        let world_position = Vec4::new(6.0, 5.0, 0.0, 1.0); // 1 unit right of the center
+
+        // when
+        let view_position = camera.world_to_view_matrix() * world_position;
+
+        // then: the center stays in place, the camera turned left so the position ends up 2 units below it
+        assert!(Vec2::new(view_position.x, view_position.y).abs_diff_eq(Vec2::new(5.0, 3.0), 1e-5));
+    }
+
+    #[test]
+    fn camera2d_should_convert_between_screen_and_world_coordinates() {
+        // given
+        let camera = Camera2D {
+            position: [10.0, 10.0],
+            size: [10.0, 10.0],
+            viewport_size: [200, 100],
+            scale_factor: 2.0,
+            ..Camera2D::default()
+        };
+
+        // when: the top left and the center of the screen
+        let top_left = camera.screen_to_world([0.0, 0.0]);
+        let center = camera.logical_to_world([50.0, 25.0]);
+        let screen_position = camera.world_to_screen([20.0, 15.0]);
+
+        // then
+        assert!(Vec2::from(top_left).abs_diff_eq(Vec2::new(10.0, 20.0), 1e-4));
+        assert!(Vec2::from(center).abs_diff_eq(Vec2::new(20.0, 15.0), 1e-4));
+        assert!(Vec2::from(screen_position).abs_diff_eq(Vec2::new(100.0, 50.0), 1e-3));
+    }
+
+    #[test]
+    fn camera2d_should_add_bars_around_fixed_virtual_resolution_in_letterbox_mode() {
+        // given: a 4:3 virtual resolution on a 16:9 screen
+        let camera = Camera2D {
+            size: [4.0, 3.0],
+            viewport_size: [1920, 1080],
+            scaling_mode: ScalingMode::Letterbox,
+            ..Camera2D::default()
+        };
+
+        // when
+        let view_size = camera.view_size();
+        let viewport = camera.pixel_viewport();
+
+        // then: pillarbox bars on the left and right
+        assert_eq!(view_size, [4.0, 3.0]);
+        assert_eq!(viewport, PixelRect { x: 240, y: 0, width: 1440, height: 1080 });
+    }
+
+    #[test]
+    fn camera2d_should_show_more_of_the_world_along_the_longer_axis_in_expand_mode() {
+        // given
+        let camera = Camera2D {
+            size: [10.0, 10.0],
+            viewport_size: [100, 200],
+            scaling_mode: ScalingMode::Expand,
+            ..Camera2D::default()
+        };
+
+        // when
+        let view_size = camera.view_size();
+
+        // then
+        assert_eq!(view_size, [10.0, 20.0]);
+    }
+
+    #[test]
+    fn camera2d_should_upscale_by_integer_factor_in_pixel_perfect_mode() {
+        // given: 320x180 fits 6 times in 1920x1200, both horizontally and vertically
+        let camera = Camera2D {
+            size: [320.0, 180.0],
+            viewport_size: [1920, 1200],
+            scaling_mode: ScalingMode::PixelPerfect { resolution: [320, 180] },
+            ..Camera2D::default()
+        };
+
+        // when
+        let viewport = camera.pixel_viewport();
+
+        // then
+        assert_eq!(viewport, PixelRect { x: 0, y: 60, width: 1920, height: 1080 });
+    }
+
+    #[test]
+    fn camera2d_should_use_the_whole_viewport_in_letterbox_mode_without_a_size() {
+        // given
+        let camera = Camera2D {
+            size: [0.0, 10.0],
+            viewport_size: [160, 120],
+            scaling_mode: ScalingMode::Letterbox,
+            ..Camera2D::default()
+        };
+
+        // when
+        let viewport = camera.pixel_viewport();
+
+        // then
+        assert_eq!(viewport, PixelRect { x: 0, y: 0, width: 160, height: 120 });
+    }
+
+    #[test]
+    fn camera2d_should_downscale_to_fit_a_viewport_smaller_than_the_resolution_in_pixel_perfect_mode() {
+        // given: 320x180 is twice as wide as the viewport
+        let camera = Camera2D {
+            size: [320.0, 180.0],
+            viewport_size: [160, 120],
+            scaling_mode: ScalingMode::PixelPerfect { resolution: [320, 180] },
+            ..Camera2D::default()
+        };
+
+        // when
+        let viewport = camera.pixel_viewport();
+
+        // then: it keeps its aspect ratio within the viewport
+        assert_eq!(viewport, PixelRect { x: 0, y: 15, width: 160, height: 90 });
+    }
+
+    #[test]
+    fn viewport_rect_should_tile_split_screen_without_gaps() {
+        // given: an odd surface width split into a left and a right half
+        let left = ViewportRect { x: 0.0, y: 0.0, width: 0.5, height: 1.0 };
+        let right = ViewportRect { x: 0.5, y: 0.0, width: 0.5, height: 1.0 };
+
+        // when
+        let left_pixels = left.to_pixels([1001, 600]);
+        let right_pixels = right.to_pixels([1001, 600]);
+
+        // then
+        assert_eq!(left_pixels.x + left_pixels.width, right_pixels.x);
+        assert_eq!(right_pixels.x + right_pixels.width, 1001);
+    }
+
+    #[test]
+    fn camera2d_should_render_inside_its_viewport_rect() {
+        // given: the right half of a 2000x1000 surface, letterboxed to a square
+        let camera = Camera2D {
+            position: [0.0, 0.0],
+            size: [10.0, 10.0],
+            viewport_size: [2000, 1000],
+            viewport: ViewportRect { x: 0.5, y: 0.0, width: 0.5, height: 1.0 },
+            scaling_mode: ScalingMode::Letterbox,
+            ..Camera2D::default()
+        };
+
+        // when
+        let region = camera.region();
+        let viewport = camera.pixel_viewport();
+        let center = camera.screen_to_world([1500.0, 500.0]);
+
+        // then
+        assert_eq!(region, PixelRect { x: 1000, y: 0, width: 1000, height: 1000 });
+        assert_eq!(viewport, PixelRect { x: 1000, y: 0, width: 1000, height: 1000 });
+        assert!(Vec2::from(center).abs_diff_eq(Vec2::new(5.0, 5.0), 1e-4));
+    }
+
+    #[test]
+    fn camera2d_should_use_aspect_ratio_of_its_viewport_rect() {
+        // given: the top half of a 1000x1000 surface
+        let camera = Camera2D {
+            size: [5.0, 5.0],
+            viewport_size: [1000, 1000],
+            viewport: ViewportRect { x: 0.0, y: 0.0, width: 1.0, height: 0.5 },
+            ..Camera2D::default()
+        };
+
+        // when
+        let view_size = camera.view_size();
+
+        // then
+        assert_eq!(view_size, [10.0, 5.0]);
+    }
+
+    #[test]
+    fn camera2d_should_show_logical_pixels_in_screen_space_mode() {
+        // given
+        let camera = Camera2D {
+            viewport_size: [1600, 1200],
+            scale_factor: 2.0,
+            ..Camera2D::screen_space(ScreenUnits::Logical)
+        };
+
+        // when
+        let view_size = camera.view_size();
+        let bottom_left = camera.screen_to_world([0.0, 1200.0]);
+
+        // then
+        assert_eq!(view_size, [800.0, 600.0]);
+        assert!(Vec2::from(bottom_left).abs_diff_eq(Vec2::new(0.0, 0.0), 1e-4));
+    }
+
+    #[test]
+    fn camera2d_should_place_elements_at_anchors() {
+        // given
+        let camera = Camera2D {
+            viewport_size: [800, 600],
+            ..Camera2D::screen_space(ScreenUnits::Physical)
+        };
+
+        // when
+        let top_left = camera.anchored_position(Anchor::TopLeft, [100.0, 50.0], [10.0, 10.0]);
+        let center = camera.anchored_position(Anchor::Center, [100.0, 50.0], [0.0, 0.0]);
+        let bottom_right = camera.anchored_position(Anchor::BottomRight, [100.0, 50.0], [10.0, 10.0]);
+
+        // then
+        assert_eq!(top_left, [10.0, 540.0]);
+        assert_eq!(center, [350.0, 275.0]);
+        assert_eq!(bottom_right, [690.0, 10.0]);
+    }
+
+    #[test]
+    fn camera2d_should_shrink_visible_bounds_when_zooming_in() {
+        // given
+        let camera = Camera2D {
+            position: [10.0, 10.0],
+            size: [10.0, 10.0],
+            viewport_size: [100, 100],
+            zoom: 2.0,
+            ..Camera2D::default()
+        };
+
+        // when
+        let bounds = camera.visible_bounds();
+
+        // then: a quarter of the view, around its center
+        assert!(Vec2::from(bounds.min).abs_diff_eq(Vec2::new(12.5, 12.5), 1e-4));
+        assert!(Vec2::from(bounds.max).abs_diff_eq(Vec2::new(17.5, 17.5), 1e-4));
+    }
+
+    #[test]
+    fn camera2d_should_flip_the_view_vertically_for_render_targets() {
+        // given
+        let camera = Camera2D {
+            size: [10.0, 10.0],
+            viewport_size: [10, 10],
+            target: Some(Rc::new(Texture::render_target(String::from("Minimap"), 10, 10))),
+            ..Camera2D::default()
+        };
+
+        // when
+        let top_left = camera.world_to_clip_matrix() * Vec4::new(0.0, 10.0, 0.0, 1.0);
+
+        // then: the top of the view ends up in the last row, which is the top of a texture stored bottom row first
+        assert_eq!([top_left.x, top_left.y], [-1.0, -1.0]);
+    }
+}
@@ -0,0 +1,287 @@
+use std::time::Duration;
+use glam::Vec2;
+use crate::engine::renderer::camera::Camera2D;
+use crate::engine::renderer::transform::Transform2D;
+
+/// Moves a camera each frame. Controllers can be combined by updating them one after the other,
+/// e.g. a `FollowController`, then a `BoundsController`, then a `CameraShake`.
+pub trait CameraController {
+    fn update(&mut self, camera: &mut Camera2D, delta_time: Duration);
+}
+
+/// Keeps a target in view, without moving the camera while the target stays inside the dead zone.
+#[derive(Debug, Clone)]
+pub struct FollowController {
+    pub dead_zone: [f32; 2], // world units, the size of the area around the center in which the target can move freely
+    pub look_ahead: f32, // seconds, how far ahead of the target to look based on its current velocity
+    pub smooth_time: f32, // seconds, roughly the time to catch up with the target, 0 to follow it rigidly
+    target: Option<Vec2>,
+    previous_target: Option<Vec2>,
+    velocity: Vec2
+}
+
+impl FollowController {
+
+    pub fn new(dead_zone: [f32; 2], look_ahead: f32, smooth_time: f32) -> Self {
+        Self {
+            dead_zone,
+            look_ahead,
+            smooth_time,
+            target: None,
+            previous_target: None,
+            velocity: Vec2::ZERO
+        }
+    }
+
+    /// Sets the transform to follow, call this every frame before updating the controller.
+    pub fn follow(&mut self, target: &Transform2D) {
+        self.target = Some(Vec2::from(target.center()));
+    }
+}
+
+impl CameraController for FollowController {
+    fn update(&mut self, camera: &mut Camera2D, delta_time: Duration) {
+        let Some(target) = self.target else {
+            return;
+        };
+        let delta_seconds = delta_time.as_secs_f32();
+
+        let target_velocity = match self.previous_target {
+            Some(previous_target) if delta_seconds > 0.0 => (target - previous_target) / delta_seconds,
+            _ => Vec2::ZERO
+        };
+        self.previous_target = Some(target);
+        let desired = target + target_velocity * self.look_ahead;
+
+        // only move as far as needed to bring the desired position back to the edge of the dead zone
+        let center = Vec2::from(camera.center());
+        let distance = desired - center;
+        let half_dead_zone = Vec2::from(self.dead_zone) / 2.0;
+        let overshoot = (distance.abs() - half_dead_zone).max(Vec2::ZERO) * distance.signum();
+        let goal = center + overshoot;
+
+        let new_center = smooth_damp(center, goal, &mut self.velocity, self.smooth_time, delta_seconds);
+        camera.set_center(new_center.into());
+    }
+}
+
+/// Keeps the visible part of the world inside the given bounds. Centers the camera when the bounds are
+/// smaller than what is visible. Camera rotation is not taken into account.
+#[derive(Debug, Clone)]
+pub struct BoundsController {
+    pub min: [f32; 2], // world units, lower left corner
+    pub max: [f32; 2] // world units, upper right corner
+}
+
+impl CameraController for BoundsController {
+    fn update(&mut self, camera: &mut Camera2D, delta_time: Duration) {
+        let half_visible = Vec2::from(camera.visible_size()) / 2.0;
+        let min = Vec2::from(self.min) + half_visible;
+        let max = Vec2::from(self.max) - half_visible;
+        let center = Vec2::from(camera.center());
+
+        let clamp_axis = |value: f32, min: f32, max: f32| if min > max { (min + max) / 2.0 } else { value.clamp(min, max) };
+        camera.set_center([
+            clamp_axis(center.x, min.x, max.x),
+            clamp_axis(center.y, min.y, max.y)
+        ]);
+    }
+}
+
+/// Trauma based screen shake: trauma is added on impacts and decays over time,
+/// the shake intensity grows with the square of the trauma.
+#[derive(Debug, Clone)]
+pub struct CameraShake {
+    pub max_offset: [f32; 2], // world units
+    pub max_rotation: f32, // radians
+    pub decay: f32, // trauma lost per second
+    pub frequency: f32, // shakes per second
+    trauma: f32,
+    time: f32
+}
+
+impl CameraShake {
+
+    pub fn new(max_offset: [f32; 2], max_rotation: f32, decay: f32, frequency: f32) -> Self {
+        Self {
+            max_offset,
+            max_rotation,
+            decay,
+            frequency,
+            trauma: 0.0,
+            time: 0.0
+        }
+    }
+
+    /// Adds trauma, clamped to the range 0 to 1.
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+
+    pub fn trauma(&self) -> f32 {
+        self.trauma
+    }
+}
+
+impl CameraController for CameraShake {
+    fn update(&mut self, camera: &mut Camera2D, delta_time: Duration) {
+        let delta_seconds = delta_time.as_secs_f32();
+        self.time += delta_seconds;
+        self.trauma = (self.trauma - self.decay * delta_seconds).max(0.0);
+
+        let intensity = self.trauma * self.trauma;
+        let phase = self.time * self.frequency;
+        camera.offset = [
+            self.max_offset[0] * intensity * smooth_noise(phase, 0.0),
+            self.max_offset[1] * intensity * smooth_noise(phase, 17.0)
+        ];
+        camera.offset_rotation = self.max_rotation * intensity * smooth_noise(phase, 43.0);
+    }
+}
+
+/// Moves a value towards a goal like a critically damped spring, without overshooting it.
+/// See Game Programming Gems 4, chapter 1.10.
+fn smooth_damp(current: Vec2, goal: Vec2, velocity: &mut Vec2, smooth_time: f32, delta_seconds: f32) -> Vec2 {
+    if smooth_time <= 0.0 || delta_seconds <= 0.0 {
+        *velocity = Vec2::ZERO;
+        return if delta_seconds > 0.0 { goal } else { current };
+    }
+
+    let omega = 2.0 / smooth_time;
+    let x = omega * delta_seconds;
+    let exp = 1.0 / (1.0 + x + 0.48 * x * x + 0.235 * x * x * x);
+    let change = current - goal;
+    let temp = (*velocity + omega * change) * delta_seconds;
+    *velocity = (*velocity - omega * temp) * exp;
+    goal + (change + temp) * exp
+}
+
+/// Smoothly varying pseudo random value between -1 and 1, different for every seed.
+fn smooth_noise(phase: f32, seed: f32) -> f32 {
+    ((phase + seed).sin() + (phase * 2.3 + seed * 1.7).sin() * 0.5) / 1.5
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use crate::engine::renderer::camera::Camera2D;
+    use crate::engine::renderer::camera_controller::{BoundsController, CameraController, CameraShake, FollowController};
+    use crate::engine::renderer::transform::Transform2D;
+
+    fn camera_centered_at(center: [f32; 2]) -> Camera2D {
+        let mut camera = Camera2D {
+            size: [10.0, 10.0],
+            viewport_size: [100, 100],
+            ..Camera2D::default()
+        };
+        camera.set_center(center);
+        camera
+    }
+
+    fn target_centered_at(center: [f32; 2]) -> Transform2D {
+        Transform2D {
+            position: [center[0] - 0.5, center[1] - 0.5],
+            ..Transform2D::default()
+        }
+    }
+
+    #[test]
+    fn follow_controller_should_not_move_while_target_is_inside_dead_zone() {
+        // given
+        let mut camera = camera_centered_at([0.0, 0.0]);
+        let mut follow = FollowController::new([4.0, 4.0], 0.0, 0.0);
+
+        // when
+        follow.follow(&target_centered_at([1.5, -1.5]));
+        follow.update(&mut camera, Duration::from_millis(16));
+
+        // then
+        assert_eq!(camera.center(), [0.0, 0.0]);
+    }
+
+    #[test]
+    fn follow_controller_should_move_target_back_to_dead_zone_edge() {
+        // given
+        let mut camera = camera_centered_at([0.0, 0.0]);
+        let mut follow = FollowController::new([4.0, 4.0], 0.0, 0.0);
+
+        // when
+        follow.follow(&target_centered_at([5.0, 0.0]));
+        follow.update(&mut camera, Duration::from_millis(16));
+
+        // then
+        assert_eq!(camera.center(), [3.0, 0.0]);
+    }
+
+    #[test]
+    fn follow_controller_should_converge_smoothly_without_overshooting() {
+        // given
+        let mut camera = camera_centered_at([0.0, 0.0]);
+        let mut follow = FollowController::new([0.0, 0.0], 0.0, 0.2);
+        let target = target_centered_at([10.0, 0.0]);
+
+        // when
+        let mut positions = Vec::new();
+        for _ in 0..120 {
+            follow.follow(&target);
+            follow.update(&mut camera, Duration::from_millis(16));
+            positions.push(camera.center()[0]);
+        }
+
+        // then
+        assert!(positions.windows(2).all(|pair| pair[0] <= pair[1] && pair[1] <= 10.0));
+        assert!((positions.last().unwrap() - 10.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn follow_controller_should_look_ahead_in_direction_of_movement() {
+        // given
+        let mut camera = camera_centered_at([0.0, 0.0]);
+        let mut follow = FollowController::new([0.0, 0.0], 0.5, 0.0);
+        follow.follow(&target_centered_at([0.0, 0.0]));
+        follow.update(&mut camera, Duration::from_secs(1));
+
+        // when: the target moves 2 units per second
+        follow.follow(&target_centered_at([2.0, 0.0]));
+        follow.update(&mut camera, Duration::from_secs(1));
+
+        // then
+        assert_eq!(camera.center(), [3.0, 0.0]);
+    }
+
+    #[test]
+    fn bounds_controller_should_keep_visible_area_inside_bounds() {
+        // given
+        let mut camera = camera_centered_at([1.0, 48.0]);
+        let mut bounds = BoundsController {
+            min: [0.0, 0.0],
+            max: [50.0, 50.0]
+        };
+
+        // when
+        bounds.update(&mut camera, Duration::from_millis(16));
+
+        // then
+        assert_eq!(camera.center(), [5.0, 45.0]);
+    }
+
+    #[test]
+    fn camera_shake_should_decay_until_camera_is_at_rest() {
+        // given
+        let mut camera = camera_centered_at([0.0, 0.0]);
+        let mut shake = CameraShake::new([1.0, 1.0], 0.1, 1.0, 20.0);
+        shake.add_trauma(1.0);
+
+        // when
+        shake.update(&mut camera, Duration::from_millis(100));
+        let shaking_offset = camera.offset;
+        shake.update(&mut camera, Duration::from_secs(1));
+
+        // then
+        assert_ne!(shaking_offset, [0.0, 0.0]);
+        assert_eq!(shake.trauma(), 0.0);
+        assert_eq!(camera.offset, [0.0, 0.0]);
+        assert_eq!(camera.offset_rotation, 0.0);
+        assert_eq!(camera.center(), [0.0, 0.0]);
+    }
+}
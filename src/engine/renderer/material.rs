@@ -1,7 +1,21 @@
+use std::path::PathBuf;
+use std::rc::Rc;
+use crate::engine::renderer::material_params::MaterialParams;
+use crate::engine::renderer::texture_region::TextureRegion;
+
 #[derive(Debug, Clone)]
 pub struct Material {
-    pub shader: &'static ShaderDefinition,
-    pub texture: Option<Texture>
+    pub shader: Rc<ShaderDefinition>, // shared, so that materials with the same shader share its pipelines
+    pub texture: Option<Rc<Texture>>, // shared, the renderer frees its GPU copy once the last handle is dropped
+    pub region: Option<TextureRegion>, // the part of the texture to draw, the whole texture when None
+    pub params: MaterialParams // values for the shader, bound to group 2 unless empty
+}
+
+impl Material {
+    /// The main texture and the textures of the parameters, everything the material samples.
+    pub fn textures(&self) -> impl Iterator<Item = &Rc<Texture>> {
+        self.texture.iter().chain(self.params.textures())
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -9,11 +23,138 @@ pub struct Texture {
     pub name: String,
     pub width: u32,
     pub height: u32,
-    pub data: Vec<u8> // RGBA pixel data
+    pub data: Vec<u8>, // RGBA pixel data, empty for render targets
+    pub sampler: SamplerOptions,
+    pub mipmaps: bool, // generate smaller copies on upload, for textures that are drawn scaled down
+    pub color_space: ColorSpace,
+    pub kind: TextureKind
+}
+
+impl Texture {
+
+    /// A texture with RGBA pixels stored bottom row first, sampled with the default sampler, in sRGB and without mipmaps.
+    /// Change the other fields afterwards for textures that are sampled differently.
+    pub fn from_pixels(name: String, width: u32, height: u32, data: Vec<u8>) -> Self {
+        Self {
+            name,
+            width,
+            height,
+            data,
+            sampler: SamplerOptions::default(),
+            mipmaps: false,
+            color_space: ColorSpace::Srgb,
+            kind: TextureKind::Pixels
+        }
+    }
+
+    /// A texture that cameras render to and sprites sample afterwards, e.g. for minimaps, mirrors and portals.
+    /// It has no pixel data, its contents only exist on the GPU.
+    pub fn render_target(name: String, width: u32, height: u32) -> Self {
+        Self {
+            name,
+            width,
+            height,
+            data: Vec::new(),
+            sampler: SamplerOptions::default(),
+            mipmaps: false,
+            color_space: ColorSpace::Srgb,
+            kind: TextureKind::RenderTarget
+        }
+    }
+}
+
+#[cfg(test)]
+impl Texture {
+    /// A texture of the default kind with every byte set to the same value, for tests.
+    pub(crate) fn filled(name: &str, width: u32, height: u32, value: u8) -> Self {
+        Self::from_pixels(String::from(name), width, height, vec![value; width as usize * height as usize * 4])
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum TextureKind {
+    /// Uploaded from the pixels in `data`.
+    Pixels,
+    /// Drawn by the renderer, without `data`. See `Camera2D::target`.
+    RenderTarget
+}
+
+/// How the values of a texture are interpreted when it is sampled.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum ColorSpace {
+    /// Colours as painted in image editors, converted to linear values by the GPU when sampled.
+    Srgb,
+    /// Values that are used as they are, e.g. normal maps, data textures and lookup tables.
+    Linear
+}
+
+/// How a texture is sampled between and beyond its pixels.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct SamplerOptions {
+    pub mag_filter: FilterMode, // when the texture is drawn larger than its size
+    pub min_filter: FilterMode, // when the texture is drawn smaller than its size
+    pub mipmap_filter: FilterMode, // between mipmap levels
+    pub address_mode_u: AddressMode, // horizontally outside of 0 - 1
+    pub address_mode_v: AddressMode, // vertically outside of 0 - 1
+    pub anisotropy: u16 // 1 - 16, values above 1 sharpen textures seen at an angle and require linear filters
+}
+
+impl SamplerOptions {
+    /// Sharp pixels without blurring, for pixel art.
+    pub const PIXEL_ART: SamplerOptions = SamplerOptions {
+        mag_filter: FilterMode::Nearest,
+        min_filter: FilterMode::Nearest,
+        mipmap_filter: FilterMode::Nearest,
+        address_mode_u: AddressMode::ClampToEdge,
+        address_mode_v: AddressMode::ClampToEdge,
+        anisotropy: 1
+    };
+
+    /// Smooth blending between pixels and between mipmap levels, for scaled down textures with mipmaps.
+    pub const TRILINEAR: SamplerOptions = SamplerOptions {
+        mag_filter: FilterMode::Linear,
+        min_filter: FilterMode::Linear,
+        mipmap_filter: FilterMode::Linear,
+        address_mode_u: AddressMode::ClampToEdge,
+        address_mode_v: AddressMode::ClampToEdge,
+        anisotropy: 1
+    };
+}
+
+impl Default for SamplerOptions {
+    fn default() -> Self {
+        Self {
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Nearest,
+            mipmap_filter: FilterMode::Nearest,
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            anisotropy: 1
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum FilterMode {
+    /// The closest pixel, blocky when magnified.
+    Nearest,
+    /// A blend of the surrounding pixels, smooth when magnified.
+    Linear
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum AddressMode {
+    /// Repeats the edge pixels.
+    ClampToEdge,
+    /// Tiles the texture.
+    Repeat,
+    /// Tiles the texture, mirrored every other time.
+    MirrorRepeat
 }
 
 #[derive(Debug, Clone)]
 pub struct ShaderDefinition {
     pub name: String,
-    pub source: String
+    pub source: String, // WGSL
+    pub path: Option<PathBuf> // the file the source was loaded from, recompiled when it changes in debug builds
 }
\ No newline at end of file
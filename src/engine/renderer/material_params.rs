@@ -0,0 +1,157 @@
+use std::rc::Rc;
+use crate::engine::renderer::material::Texture;
+
+/// A value that a material passes to its shader.
+#[derive(Debug, Clone)]
+pub enum MaterialValue {
+    Float(f32),
+    Vec2([f32; 2]),
+    Vec3([f32; 3]),
+    Vec4([f32; 4]),
+    /// RGBA in the same linear space as the vertex colours, a `vec4f` in the shader.
+    Color([f32; 4]),
+    /// A texture and its sampler, in addition to the material's main texture.
+    Texture(Rc<Texture>)
+}
+
+/// Named values that a material passes to its shader, e.g. the progress of a dissolve or the colour of an outline.
+/// They are bound to group 2: the values that aren't textures are packed into a uniform struct at binding 0,
+/// in the order they were first set, followed by a texture and a sampler binding per texture in the same order.
+///
+/// A material with a float `dissolve`, a colour `edge_color` and a texture `noise` is declared in WGSL as:
+///
+/// ```wgsl
+/// struct MaterialParams {
+///     dissolve: f32,
+///     edge_color: vec4f,
+/// }
+///
+/// @group(2) @binding(0)
+/// var<uniform> material: MaterialParams;
+/// @group(2) @binding(1)
+/// var noise: texture_2d<f32>;
+/// @group(2) @binding(2)
+/// var noise_sampler: sampler;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MaterialParams {
+    entries: Vec<(String, MaterialValue)>
+}
+
+impl MaterialParams {
+
+    /// Sets a value, replacing the one of the same name in place or adding it after the others.
+    /// Replacing a value with one of another type changes the layout the shader has to declare.
+    pub fn set(&mut self, name: &str, value: MaterialValue) {
+        match self.entries.iter_mut().find(|(entry_name, _)| entry_name == name) {
+            Some((_, entry_value)) => *entry_value = value,
+            None => self.entries.push((name.to_string(), value))
+        }
+    }
+
+    /// Sets a value while building a material, see `set`.
+    pub fn with(mut self, name: &str, value: MaterialValue) -> Self {
+        self.set(name, value);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&MaterialValue> {
+        self.entries.iter().find(|(entry_name, _)| entry_name == name).map(|(_, value)| value)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The names and values in binding order.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &MaterialValue)> {
+        self.entries.iter().map(|(name, value)| (name.as_str(), value))
+    }
+
+    /// The textures in binding order, the first one is bound at binding 1 and its sampler at binding 2.
+    pub fn textures(&self) -> impl Iterator<Item = &Rc<Texture>> {
+        self.entries.iter().filter_map(|(_, value)| match value {
+            MaterialValue::Texture(texture) => Some(texture),
+            _ => None
+        })
+    }
+
+    /// The values that aren't textures laid out like a WGSL uniform struct: vec2 aligned to 8 bytes,
+    /// vec3 and vec4 to 16 bytes and the whole struct padded to a multiple of 16 bytes, at least 16.
+    pub fn uniform_bytes(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::new();
+        for (_, value) in &self.entries {
+            let (alignment, components): (usize, &[f32]) = match value {
+                MaterialValue::Float(value) => (4, std::slice::from_ref(value)),
+                MaterialValue::Vec2(value) => (8, value),
+                MaterialValue::Vec3(value) => (16, value),
+                MaterialValue::Vec4(value) | MaterialValue::Color(value) => (16, value),
+                MaterialValue::Texture(_) => continue
+            };
+            bytes.resize(bytes.len().next_multiple_of(alignment), 0);
+            for component in components {
+                bytes.extend_from_slice(&component.to_ne_bytes());
+            }
+        }
+        bytes.resize(bytes.len().next_multiple_of(16).max(16), 0);
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use crate::engine::renderer::material::Texture;
+    use crate::engine::renderer::material_params::{MaterialParams, MaterialValue};
+
+    fn floats(bytes: &[u8]) -> Vec<f32> {
+        bytes.chunks(4).map(|chunk| f32::from_ne_bytes(chunk.try_into().unwrap())).collect()
+    }
+
+    #[test]
+    fn uniform_bytes_should_align_vectors_like_wgsl() {
+        // given
+        let params = MaterialParams::default()
+            .with("dissolve", MaterialValue::Float(0.5))
+            .with("offset", MaterialValue::Vec2([1.0, 2.0]))
+            .with("edge_color", MaterialValue::Color([0.1, 0.2, 0.3, 1.0]))
+            .with("glow", MaterialValue::Float(3.0));
+
+        // when
+        let bytes = params.uniform_bytes();
+
+        // then: the vec2 starts at 8 and the colour at 16, the struct is padded to 48 bytes
+        assert_eq!(floats(&bytes), vec![0.5, 0.0, 1.0, 2.0, 0.1, 0.2, 0.3, 1.0, 3.0, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn uniform_bytes_should_pack_floats_after_a_vec3() {
+        // given
+        let params = MaterialParams::default()
+            .with("tint", MaterialValue::Vec3([1.0, 2.0, 3.0]))
+            .with("strength", MaterialValue::Float(4.0));
+
+        // then: a float fills the gap at the end of a vec3
+        assert_eq!(floats(&params.uniform_bytes()), vec![1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(MaterialParams::default().uniform_bytes().len(), 16);
+    }
+
+    #[test]
+    fn set_should_replace_values_in_place_and_skip_textures_in_the_uniforms() {
+        // given
+        let noise = Rc::new(Texture::render_target(String::from("Noise"), 4, 4));
+        let mut params = MaterialParams::default()
+            .with("flash", MaterialValue::Float(0.0))
+            .with("noise", MaterialValue::Texture(Rc::clone(&noise)))
+            .with("outline", MaterialValue::Float(2.0));
+
+        // when
+        params.set("flash", MaterialValue::Float(1.0));
+
+        // then
+        let names: Vec<&str> = params.entries().map(|(name, _)| name).collect();
+        assert_eq!(names, vec!["flash", "noise", "outline"]);
+        assert_eq!(floats(&params.uniform_bytes()), vec![1.0, 2.0, 0.0, 0.0]);
+        assert!(params.textures().all(|texture| Rc::ptr_eq(texture, &noise)));
+    }
+}
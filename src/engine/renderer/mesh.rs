@@ -1,9 +1,18 @@
+use crate::engine::renderer::bounds::Aabb;
+
 #[derive(Debug, Clone)]
 pub struct Mesh {
     pub name: String,
     pub vertices: Vec<Vertex>
 }
 
+impl Mesh {
+    /// The bounds of the vertices in local space, `None` for a mesh without vertices.
+    pub fn bounds(&self) -> Option<Aabb> {
+        Aabb::from_points(self.vertices.iter().map(|vertex| [vertex.position[0], vertex.position[1]]))
+    }
+}
+
 #[repr(C)]  // Guarantees consistent memory layout across platforms
 #[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct Vertex {
@@ -0,0 +1,162 @@
+use crate::engine::renderer::material::{ColorSpace, Texture};
+
+const BYTES_PER_PIXEL: usize = 4;
+
+/// A smaller copy of a texture, half the size of the level before it.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MipLevel {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8> // RGBA pixel data, stored bottom row first like the texture
+}
+
+/// The number of levels down to 1x1, including the texture itself.
+pub fn mip_level_count(width: u32, height: u32) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
+
+/// Generates the levels below the texture itself down to 1x1, each from the one before it.
+/// Every pixel averages 2x2 pixels of the larger level, weighted by their alpha so that transparent pixels
+/// don't darken the edges of sprites. sRGB colours are averaged after converting them to linear values.
+pub fn generate_mipmaps(texture: &Texture) -> Vec<MipLevel> {
+    generate_mipmaps_of(texture.width, texture.height, &texture.data, texture.color_space)
+}
+
+/// Generates the levels below RGBA pixels of the given size, like `generate_mipmaps`.
+pub fn generate_mipmaps_of(width: u32, height: u32, data: &[u8], color_space: ColorSpace) -> Vec<MipLevel> {
+    let mut levels: Vec<MipLevel> = Vec::new();
+    for _ in 1..mip_level_count(width, height) {
+        let next = match levels.last() {
+            Some(previous) => downsample(previous.width, previous.height, &previous.data, color_space),
+            None => downsample(width, height, data, color_space)
+        };
+        levels.push(next);
+    }
+    levels
+}
+
+fn downsample(width: u32, height: u32, data: &[u8], color_space: ColorSpace) -> MipLevel {
+    let next_width = (width / 2).max(1);
+    let next_height = (height / 2).max(1);
+    let mut next_data = Vec::with_capacity(next_width as usize * next_height as usize * BYTES_PER_PIXEL);
+
+    for row in 0..next_height {
+        for column in 0..next_width {
+            // odd sizes leave out the last row or column, a 1 pixel side is kept as it is
+            let source_pixels = [(0, 0), (1, 0), (0, 1), (1, 1)].map(|(dx, dy)| {
+                let x = (column * 2 + dx).min(width - 1) as usize;
+                let y = (row * 2 + dy).min(height - 1) as usize;
+                let offset = (y * width as usize + x) * BYTES_PER_PIXEL;
+                &data[offset..offset + BYTES_PER_PIXEL]
+            });
+            next_data.extend_from_slice(&average(&source_pixels, color_space));
+        }
+    }
+
+    MipLevel {
+        width: next_width,
+        height: next_height,
+        data: next_data
+    }
+}
+
+fn average(pixels: &[&[u8]; 4], color_space: ColorSpace) -> [u8; 4] {
+    let mut color = [0.0f32; 3];
+    let mut alpha = 0.0;
+    for pixel in pixels {
+        let pixel_alpha = pixel[3] as f32 / 255.0;
+        for channel in 0..3 {
+            color[channel] += to_linear(pixel[channel], color_space) * pixel_alpha;
+        }
+        alpha += pixel_alpha;
+    }
+
+    if alpha == 0.0 {
+        return [0, 0, 0, 0];
+    }
+    [
+        from_linear(color[0] / alpha, color_space),
+        from_linear(color[1] / alpha, color_space),
+        from_linear(color[2] / alpha, color_space),
+        (alpha / 4.0 * 255.0).round() as u8
+    ]
+}
+
+fn to_linear(value: u8, color_space: ColorSpace) -> f32 {
+    let value = value as f32 / 255.0;
+    if color_space == ColorSpace::Linear {
+        value
+    } else if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn from_linear(value: f32, color_space: ColorSpace) -> u8 {
+    let value = if color_space == ColorSpace::Linear {
+        value
+    } else if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    };
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::renderer::material::{ColorSpace, Texture};
+    use crate::engine::renderer::mipmap::{generate_mipmaps, mip_level_count};
+
+    #[test]
+    fn mip_level_count_should_go_down_to_one_pixel() {
+        assert_eq!(mip_level_count(1, 1), 1);
+        assert_eq!(mip_level_count(256, 256), 9);
+        assert_eq!(mip_level_count(100, 30), 7);
+    }
+
+    #[test]
+    fn generate_mipmaps_should_halve_every_level() {
+        // given
+        let texture = Texture::filled("Texture", 8, 2, 255);
+
+        // when
+        let levels = generate_mipmaps(&texture);
+
+        // then
+        let sizes: Vec<[u32; 2]> = levels.iter().map(|level| [level.width, level.height]).collect();
+        assert_eq!(sizes, vec![[4, 1], [2, 1], [1, 1]]);
+        assert_eq!(levels[2].data, vec![255; 4]);
+    }
+
+    #[test]
+    fn generate_mipmaps_should_ignore_the_colour_of_transparent_pixels() {
+        // given: an opaque white pixel next to three transparent black ones
+        let mut data = vec![0; 2 * 2 * 4];
+        data[0..4].copy_from_slice(&[255, 255, 255, 255]);
+        let texture = Texture::from_pixels(String::from("Texture"), 2, 2, data);
+
+        // when
+        let levels = generate_mipmaps(&texture);
+
+        // then: still white, a quarter covered
+        assert_eq!(levels[0].data, vec![255, 255, 255, 64]);
+    }
+
+    #[test]
+    fn generate_mipmaps_should_average_srgb_colours_in_linear_light() {
+        // given: a black and a white pixel
+        let data = vec![0, 0, 0, 255, 255, 255, 255, 255];
+        let srgb = Texture::from_pixels(String::from("Texture"), 2, 1, data.clone());
+        let linear = Texture { color_space: ColorSpace::Linear, ..Texture::from_pixels(String::from("Texture"), 2, 1, data) };
+
+        // when
+        let srgb_levels = generate_mipmaps(&srgb);
+        let linear_levels = generate_mipmaps(&linear);
+
+        // then: half the light is brighter than half the sRGB value
+        assert_eq!(srgb_levels[0].data, vec![188, 188, 188, 255]);
+        assert_eq!(linear_levels[0].data, vec![128, 128, 128, 255]);
+    }
+}
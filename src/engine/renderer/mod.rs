@@ -1,24 +1,67 @@
 pub mod shaders;
 pub mod wgpu;
 pub mod material;
+pub mod material_params;
 pub mod mesh;
 pub mod transform;
+pub mod transform_hierarchy;
 pub mod camera;
+pub mod camera_controller;
+pub mod bounds;
+pub mod texture_region;
+pub mod animation;
+pub mod mipmap;
+pub mod texture_cache;
+pub mod pass_order;
+pub mod post_processing;
+pub mod shader_cache;
+pub mod shader_validation;
 
 use std::cell::{Cell, Ref};
+use glam::Mat4;
 use material::Material;
 use mesh::Mesh;
 use transform::Transform2D;
+use crate::engine::renderer::bounds::Aabb;
 use crate::engine::renderer::camera::Camera2D;
 
 pub trait Renderer {
+    /// Starts a new frame, the scenes rendered until `end_frame` are drawn on top of each other.
+    fn begin_frame(&mut self);
+
     fn render(&mut self, renderables: &dyn Scene);
+
+    /// Finishes the frame and presents it.
+    fn end_frame(&mut self);
+
+    /// Called when the render surface changed size, in physical pixels.
+    fn resize(&mut self, width: u32, height: u32);
+
+    /// Statistics of the last finished frame.
+    fn stats(&self) -> RenderStats;
+}
+
+/// What happened during a frame, summed over all scenes and cameras.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct RenderStats {
+    /// Renderables that were drawn.
+    pub drawn: u32,
+    /// Renderables that were skipped because they were outside of the camera's view.
+    pub culled: u32,
+    /// Textures whose GPU memory was freed after the frame.
+    pub evicted_textures: u32
 }
 
 pub trait Scene {
     fn get_renderables(&self) -> Vec<Renderable>;
 
-    fn get_camera(&self) -> Ref<Camera2D>;
+    fn get_camera(&self) -> Ref<'_, Camera2D>;
+
+    /// The cameras the scene is rendered with, in order, each to its own viewport rect.
+    /// Defaults to only the camera of `get_camera`, override it for split-screen, minimaps and the like.
+    fn get_cameras(&self) -> Vec<Ref<'_, Camera2D>> {
+        vec![self.get_camera()]
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -26,6 +69,73 @@ pub struct Renderable {
     pub name: String,
     pub mesh: Mesh,
     pub material: Material,
-    pub transform: Transform2D // in world space
+    pub transform: Transform2D, // in world space, unless the world matrix is set
+    /// The local to world matrix from a `TransformHierarchy`, for renderables attached to a parent.
+    /// Takes the place of the transform when set.
+    pub world_matrix: Option<Mat4>
+}
+
+impl Renderable {
+    /// The matrix from the mesh's local space to world space.
+    pub fn model_matrix(&self) -> Mat4 {
+        self.world_matrix.unwrap_or_else(|| self.transform.local_to_world_model_matrix())
+    }
+
+    /// The bounds of the mesh in world space, `None` for a mesh without vertices.
+    pub fn world_bounds(&self) -> Option<Aabb> {
+        self.mesh.bounds().map(|bounds| bounds.transformed(self.model_matrix()))
+    }
+
+    /// Whether any part of the renderable could be visible through the camera.
+    /// Computes the camera's bounds, use `is_visible_in` to test many renderables against the same camera.
+    pub fn is_visible_to(&self, camera: &Camera2D) -> bool {
+        self.is_visible_in(&camera.visible_bounds())
+    }
+
+    /// Whether any part of the renderable lies within the visible bounds of a camera.
+    pub fn is_visible_in(&self, visible_bounds: &Aabb) -> bool {
+        self.world_bounds().is_some_and(|bounds| bounds.intersects(visible_bounds))
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use crate::engine::renderer::camera::Camera2D;
+    use crate::engine::renderer::material::Material;
+    use crate::engine::renderer::material_params::MaterialParams;
+    use crate::engine::renderer::mesh::{Mesh, Vertex};
+    use crate::engine::renderer::shaders::sprite_shader;
+    use crate::engine::renderer::transform::Transform2D;
+    use crate::engine::renderer::Renderable;
+
+    fn quad_at(position: [f32; 2]) -> Renderable {
+        let vertex = |x: f32, y: f32| Vertex { position: [x, y, 0.0], color: [1.0; 4], tex_coords: [0.0; 2] };
+        Renderable {
+            name: String::from("Quad"),
+            mesh: Mesh {
+                name: String::from("Quad Mesh"),
+                vertices: vec![vertex(-0.5, -0.5), vertex(0.5, 0.5), vertex(0.5, -0.5)]
+            },
+            material: Material { shader: sprite_shader(), texture: None, region: None, params: MaterialParams::default() },
+            transform: Transform2D { position, scale: [2.0, 2.0], ..Transform2D::default() },
+            world_matrix: None
+        }
+    }
+
+    #[test]
+    fn renderable_should_only_be_visible_when_its_bounds_overlap_the_view() {
+        // given
+        let camera = Camera2D {
+            size: [10.0, 10.0],
+            viewport_size: [100, 100],
+            ..Camera2D::default()
+        };
+
+        // then
+        assert!(quad_at([4.0, 4.0]).is_visible_to(&camera));
+        assert!(quad_at([-1.5, 9.0]).is_visible_to(&camera));
+        assert!(!quad_at([-3.0, 0.0]).is_visible_to(&camera));
+        assert!(!quad_at([50.0, 50.0]).is_visible_to(&camera));
+        assert!(quad_at([4.0, 4.0]).is_visible_in(&camera.visible_bounds()));
+    }
+}
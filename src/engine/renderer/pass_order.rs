@@ -0,0 +1,107 @@
+use log::warn;
+
+/// What a render pass draws to and which textures it samples, with textures identified by keys.
+#[derive(Debug, Clone)]
+pub struct PassDependencies<K> {
+    pub target: Option<K>, // the texture the pass draws to, the surface when None
+    pub sampled: Vec<K> // the textures the pass draws with
+}
+
+/// Orders render passes so that a texture is drawn before the passes that sample it, e.g. a minimap camera
+/// before the scene that shows the minimap. Passes that draw to the same target, including the surface,
+/// keep their order, so layers still end up on top of each other as submitted.
+///
+/// # Returns
+///
+/// * `Vec<usize>` - The indices of the passes in the order to execute them. Passes that depend on each other
+///   in a cycle, like two mirrors facing each other, are kept in submission order and sample the texture
+///   as it was drawn the frame before.
+pub fn order_passes<K: Eq>(passes: &[PassDependencies<K>]) -> Vec<usize> {
+    // dependencies[pass] are the passes that have to be executed before it
+    let dependencies: Vec<Vec<usize>> = passes.iter().enumerate()
+        .map(|(pass_index, pass)| {
+            passes.iter().enumerate()
+                .filter(|(other_index, other)| *other_index != pass_index && (
+                    // drawn before sampled
+                    other.target.as_ref().is_some_and(|target| pass.sampled.contains(target))
+                    // drawn to the same target earlier
+                    || (*other_index < pass_index && other.target == pass.target)
+                ))
+                .map(|(other_index, _)| other_index)
+                .collect()
+        })
+        .collect();
+
+    let mut order = Vec::with_capacity(passes.len());
+    let mut done = vec![false; passes.len()];
+    while order.len() < passes.len() {
+        let ready = (0..passes.len())
+            .find(|pass| !done[*pass] && dependencies[*pass].iter().all(|dependency| done[*dependency]));
+        let next = ready.unwrap_or_else(|| {
+            let first_remaining = (0..passes.len()).find(|pass| !done[*pass]).unwrap();
+            warn!("Render pass {} samples a texture that depends on it, it gets the texture of the previous frame", first_remaining);
+            first_remaining
+        });
+        done[next] = true;
+        order.push(next);
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::renderer::pass_order::{order_passes, PassDependencies};
+
+    fn pass(target: Option<&'static str>, sampled: &[&'static str]) -> PassDependencies<&'static str> {
+        PassDependencies { target, sampled: sampled.to_vec() }
+    }
+
+    #[test]
+    fn order_passes_should_draw_targets_before_they_are_sampled() {
+        // given: the minimap camera of the game scene is submitted after the overlay that shows the minimap
+        let passes = [
+            pass(None, &["tiles"]),
+            pass(None, &["minimap"]),
+            pass(Some("minimap"), &["tiles"])
+        ];
+
+        // when
+        let order = order_passes(&passes);
+
+        // then
+        assert_eq!(order, vec![0, 2, 1]);
+    }
+
+    #[test]
+    fn order_passes_should_keep_the_order_of_passes_to_the_same_target() {
+        // given: the second surface pass waits for the portal, the first one has to stay before it
+        let passes = [
+            pass(None, &[]),
+            pass(None, &["portal"]),
+            pass(Some("portal"), &[]),
+            pass(None, &[])
+        ];
+
+        // when
+        let order = order_passes(&passes);
+
+        // then
+        assert_eq!(order, vec![0, 2, 1, 3]);
+    }
+
+    #[test]
+    fn order_passes_should_fall_back_to_submission_order_for_cycles() {
+        // given: two mirrors that see each other
+        let passes = [
+            pass(Some("left"), &["right"]),
+            pass(Some("right"), &["left"]),
+            pass(None, &["left", "right"])
+        ];
+
+        // when
+        let order = order_passes(&passes);
+
+        // then
+        assert_eq!(order, vec![0, 1, 2]);
+    }
+}
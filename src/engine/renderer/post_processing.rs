@@ -0,0 +1,258 @@
+use std::rc::Rc;
+use crate::engine::renderer::material::{ColorSpace, SamplerOptions, ShaderDefinition, Texture};
+use crate::engine::renderer::shaders::{bloom_combine_effect, bloom_threshold_effect, blur_effect, color_grading_effect, crt_effect, pixelate_effect, vignette_effect};
+
+/// Effects applied to the whole frame after all layers have been drawn, in order.
+/// The layers are drawn to an offscreen target first, the last effect writes the result to the surface.
+#[derive(Debug, Clone, Default)]
+pub struct PostProcessingStack {
+    pub effects: Vec<PostEffect>
+}
+
+#[derive(Debug, Clone)]
+pub enum PostEffect {
+    /// Makes bright parts glow by blurring them and adding them on top.
+    Bloom {
+        threshold: f32, // 0 - 1, brightness from which pixels glow
+        intensity: f32, // how strongly the glow is added
+        radius: f32 // spread of the blur in pixels of the half sized glow
+    },
+    /// Darkens the corners of the frame.
+    Vignette {
+        intensity: f32, // 0 - 1, how dark the corners get
+        smoothness: f32 // 0 - 1, how far towards the center the darkening starts
+    },
+    /// Maps colours through a lookup table, e.g. a neutral LUT from `neutral_lut` edited in an image editor.
+    ColorGrading {
+        lut: Rc<Texture>, // N*N x N pixels, N slices of blue side by side with red to the right and green downwards
+        strength: f32 // 0 - 1, blends between the original and the graded colours
+    },
+    /// Curves the picture and adds scanlines like an old CRT monitor.
+    Crt {
+        scanline_intensity: f32, // 0 - 1, how dark every other line gets
+        curvature: f32 // 0 is flat, 0.1 is a clearly curved screen
+    },
+    /// Shows the frame in large blocks of pixels.
+    Pixelate {
+        pixel_size: f32 // physical pixels per block
+    },
+    /// An effect of its own, e.g. loaded with `load_shader_from_file`, whose shader only provides the fragment shader. It is combined with
+    /// `shaders::POST_EFFECT_PRELUDE`, which declares the vertex shader and the bindings it can use.
+    Custom {
+        shader: Rc<ShaderDefinition>,
+        params: [f32; 4], // passed to the shader as `effect_uniforms.params`
+        texture: Option<Rc<Texture>> // bound as `extra_texture`, the frame itself when None
+    }
+}
+
+/// Where a post-processing pass reads from or draws to.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PostTarget {
+    /// The frame as drawn by the layers.
+    Scene,
+    /// Full sized targets that the effects alternate between.
+    Ping,
+    Pong,
+    /// Half sized targets for blurring the glow of the bloom effect.
+    BloomA,
+    BloomB,
+    /// The render surface, only drawn to by the last pass.
+    Surface
+}
+
+/// The second texture a pass samples.
+#[derive(Debug, Clone)]
+pub enum PostInput {
+    Target(PostTarget),
+    Texture(Rc<Texture>)
+}
+
+/// A single fullscreen draw of the post-processing chain.
+#[derive(Debug, Clone)]
+pub struct PostPass {
+    pub shader: Rc<ShaderDefinition>,
+    pub params: [f32; 4],
+    pub source: PostTarget,
+    pub extra: PostInput,
+    pub output: PostTarget
+}
+
+impl PostProcessingStack {
+
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    /// Expands the effects into fullscreen passes. Each effect reads the output of the one before it,
+    /// alternating between the ping and pong targets, and the last pass draws to the surface.
+    pub fn passes(&self) -> Vec<PostPass> {
+        let mut passes = Vec::new();
+        let mut current = PostTarget::Scene;
+        for (index, effect) in self.effects.iter().enumerate() {
+            let output = if index == self.effects.len() - 1 {
+                PostTarget::Surface
+            } else if current == PostTarget::Ping {
+                PostTarget::Pong
+            } else {
+                PostTarget::Ping
+            };
+            let pass = |shader: Rc<ShaderDefinition>, params: [f32; 4]| PostPass {
+                shader,
+                params,
+                source: current,
+                extra: PostInput::Target(current),
+                output
+            };
+
+            match effect {
+                PostEffect::Bloom { threshold, intensity, radius } => {
+                    passes.extend([
+                        PostPass { output: PostTarget::BloomA, ..pass(bloom_threshold_effect(), [*threshold, 0.0, 0.0, 0.0]) },
+                        PostPass {
+                            source: PostTarget::BloomA,
+                            output: PostTarget::BloomB,
+                            ..pass(blur_effect(), [*radius, 1.0, 0.0, 0.0]) // horizontally
+                        },
+                        PostPass {
+                            source: PostTarget::BloomB,
+                            output: PostTarget::BloomA,
+                            ..pass(blur_effect(), [*radius, 0.0, 1.0, 0.0]) // vertically
+                        },
+                        PostPass {
+                            extra: PostInput::Target(PostTarget::BloomA),
+                            ..pass(bloom_combine_effect(), [*intensity, 0.0, 0.0, 0.0])
+                        }
+                    ]);
+                },
+                PostEffect::Vignette { intensity, smoothness } =>
+                    passes.push(pass(vignette_effect(), [*intensity, *smoothness, 0.0, 0.0])),
+                PostEffect::ColorGrading { lut, strength } =>
+                    passes.push(PostPass {
+                        extra: PostInput::Texture(Rc::clone(lut)),
+                        ..pass(color_grading_effect(), [*strength, 0.0, 0.0, 0.0])
+                    }),
+                PostEffect::Crt { scanline_intensity, curvature } =>
+                    passes.push(pass(crt_effect(), [*scanline_intensity, *curvature, 0.0, 0.0])),
+                PostEffect::Pixelate { pixel_size } =>
+                    passes.push(pass(pixelate_effect(), [*pixel_size, 0.0, 0.0, 0.0])),
+                PostEffect::Custom { shader, params, texture } =>
+                    passes.push(PostPass {
+                        extra: texture.as_ref().map_or(PostInput::Target(current), |texture| PostInput::Texture(Rc::clone(texture))),
+                        ..pass(Rc::clone(shader), *params)
+                    })
+            }
+            current = output;
+        }
+        passes
+    }
+}
+
+/// A lookup table that leaves colours as they are, as a starting point for colour grading.
+/// Take a screenshot of the game, grade it in an image editor and apply the same adjustments to this table.
+///
+/// # Arguments
+///
+/// * `size` - Steps per colour channel, 16 or 32 are common
+pub fn neutral_lut(size: u32) -> Texture {
+    let width = size * size;
+    let max = (size - 1).max(1) as f32;
+    let mut data = Vec::with_capacity((width * size * 4) as usize);
+    // stored bottom row first, the bottom row has the most green
+    for row in (0..size).rev() {
+        for column in 0..width {
+            let red = (column % size) as f32 / max;
+            let green = row as f32 / max;
+            let blue = (column / size) as f32 / max;
+            data.extend([red, green, blue].map(|channel| (channel * 255.0).round() as u8));
+            data.push(255);
+        }
+    }
+
+    Texture {
+        sampler: SamplerOptions::TRILINEAR, // blends between the steps of the table
+        color_space: ColorSpace::Linear,
+        ..Texture::from_pixels(format!("neutral_lut_{}", size), width, size, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use crate::engine::renderer::post_processing::{neutral_lut, PostEffect, PostInput, PostProcessingStack, PostTarget};
+
+    fn targets(stack: &PostProcessingStack) -> Vec<(PostTarget, PostTarget)> {
+        stack.passes().iter().map(|pass| (pass.source, pass.output)).collect()
+    }
+
+    #[test]
+    fn passes_should_alternate_targets_and_end_on_the_surface() {
+        // given
+        let stack = PostProcessingStack {
+            effects: vec![
+                PostEffect::Pixelate { pixel_size: 4.0 },
+                PostEffect::Vignette { intensity: 0.5, smoothness: 0.5 },
+                PostEffect::Crt { scanline_intensity: 0.3, curvature: 0.1 }
+            ]
+        };
+
+        // then
+        assert_eq!(targets(&stack), vec![
+            (PostTarget::Scene, PostTarget::Ping),
+            (PostTarget::Ping, PostTarget::Pong),
+            (PostTarget::Pong, PostTarget::Surface)
+        ]);
+    }
+
+    #[test]
+    fn passes_should_blur_the_bloom_in_its_own_targets() {
+        // given
+        let stack = PostProcessingStack {
+            effects: vec![PostEffect::Bloom { threshold: 0.8, intensity: 1.0, radius: 1.0 }]
+        };
+
+        // when
+        let passes = stack.passes();
+
+        // then: the glow is extracted, blurred twice and added to the scene
+        assert_eq!(targets(&stack), vec![
+            (PostTarget::Scene, PostTarget::BloomA),
+            (PostTarget::BloomA, PostTarget::BloomB),
+            (PostTarget::BloomB, PostTarget::BloomA),
+            (PostTarget::Scene, PostTarget::Surface)
+        ]);
+        assert!(matches!(passes[3].extra, PostInput::Target(PostTarget::BloomA)));
+    }
+
+    #[test]
+    fn passes_should_sample_the_lut_of_colour_grading() {
+        // given
+        let lut = Rc::new(neutral_lut(16));
+        let stack = PostProcessingStack {
+            effects: vec![PostEffect::ColorGrading { lut: Rc::clone(&lut), strength: 1.0 }]
+        };
+
+        // when
+        let passes = stack.passes();
+
+        // then
+        assert!(matches!(&passes[0].extra, PostInput::Texture(texture) if Rc::ptr_eq(texture, &lut)));
+    }
+
+    #[test]
+    fn neutral_lut_should_lay_out_blue_slices_side_by_side() {
+        // given
+        let lut = neutral_lut(4);
+        let pixel = |column: u32, row_from_top: u32| {
+            let offset = (((lut.height - 1 - row_from_top) * lut.width + column) * 4) as usize;
+            lut.data[offset..offset + 3].to_vec()
+        };
+
+        // then
+        assert_eq!([lut.width, lut.height], [16, 4]);
+        assert_eq!(pixel(0, 0), vec![0, 0, 0]);
+        assert_eq!(pixel(3, 0), vec![255, 0, 0]);
+        assert_eq!(pixel(0, 3), vec![0, 255, 0]);
+        assert_eq!(pixel(15, 3), vec![255, 255, 255]);
+        assert_eq!(pixel(4, 0), vec![0, 0, 85]);
+    }
+}
@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::path::{Path, PathBuf};
+use std::rc::{Rc, Weak};
+use std::time::{Duration, Instant, SystemTime};
+use log::warn;
+use crate::engine::renderer::material::ShaderDefinition;
+
+/// The pipelines of shared shaders, compiled once per variant, e.g. per target format.
+/// Keeps the source each shader was compiled from, which is replaced when the shader's file changes.
+pub struct ShaderCache<K, P> {
+    entries: HashMap<usize, ShaderEntry<K, P>> // by address of the shader
+}
+
+struct ShaderEntry<K, P> {
+    shader: Weak<ShaderDefinition>, // only the cache's view, the entry is dropped after the last material released it
+    source: String,
+    modified: Option<SystemTime>, // of the shader's file when it was last read
+    pipelines: HashMap<K, Option<P>> // None for variants that failed to compile, so that they aren't retried every frame
+}
+
+/// A shader whose file changed, with the variants that have to be compiled from its new source.
+pub struct ShaderChange<K> {
+    pub shader: Rc<ShaderDefinition>,
+    pub source: String,
+    pub variants: Vec<K>
+}
+
+impl<K: Eq + Hash + Clone, P> ShaderCache<K, P> {
+
+    pub fn new() -> Self {
+        Self { entries: HashMap::new() }
+    }
+
+    /// The source to compile the shader from: the latest version of its file that compiled, or its own source.
+    pub fn source(&mut self, shader: &Rc<ShaderDefinition>) -> &str {
+        &self.entry(shader).source
+    }
+
+    /// Whether the variant was compiled already, successfully or not.
+    pub fn contains(&self, shader: &Rc<ShaderDefinition>, variant: &K) -> bool {
+        self.live_entry(shader).is_some_and(|entry| entry.pipelines.contains_key(variant))
+    }
+
+    /// The pipeline of a variant, None when it wasn't compiled yet or failed to compile.
+    pub fn get(&self, shader: &Rc<ShaderDefinition>, variant: &K) -> Option<&P> {
+        self.live_entry(shader)?.pipelines.get(variant)?.as_ref()
+    }
+
+    /// Stores the result of compiling a variant, None if it failed.
+    pub fn insert(&mut self, shader: &Rc<ShaderDefinition>, variant: K, pipeline: Option<P>) {
+        self.entry(shader).pipelines.insert(variant, pipeline);
+    }
+
+    /// Reads the files of the shaders that changed since they were last read. Each change is reported once,
+    /// whether or not its source compiles, so a broken shader is retried when its file is saved again.
+    ///
+    /// # Arguments
+    ///
+    /// * `files` - The modification times of the current poll, shared by the caches of all renderers
+    pub fn poll_changes(&mut self, files: &mut ShaderFileWatcher) -> Vec<ShaderChange<K>> {
+        let mut changes = Vec::new();
+        for entry in self.entries.values_mut() {
+            let Some(shader) = entry.shader.upgrade() else {
+                continue;
+            };
+            let Some(path) = shader.path.as_ref() else {
+                continue;
+            };
+            let modified = files.modified(path);
+            if modified.is_none() || modified == entry.modified {
+                continue;
+            }
+            entry.modified = modified;
+
+            match std::fs::read_to_string(path) {
+                Ok(source) => changes.push(ShaderChange {
+                    variants: entry.pipelines.keys().cloned().collect(),
+                    shader,
+                    source
+                }),
+                Err(error) => warn!("Shader {} could not be read from {}: {}", shader.name, path.display(), error)
+            }
+        }
+        changes
+    }
+
+    /// Replaces the source and the pipelines of a shader that was recompiled.
+    pub fn replace(&mut self, shader: &Rc<ShaderDefinition>, source: String, pipelines: Vec<(K, P)>) {
+        let entry = self.entry(shader);
+        entry.source = source;
+        entry.pipelines = pipelines.into_iter().map(|(variant, pipeline)| (variant, Some(pipeline))).collect();
+    }
+
+    /// Drops the pipelines of shaders that are no longer used by any material.
+    pub fn collect(&mut self) {
+        self.entries.retain(|_, entry| entry.shader.strong_count() > 0);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn live_entry(&self, shader: &Rc<ShaderDefinition>) -> Option<&ShaderEntry<K, P>> {
+        self.entries.get(&shader_key(shader))
+            .filter(|entry| entry.shader.upgrade().is_some_and(|cached| Rc::ptr_eq(&cached, shader)))
+    }
+
+    fn entry(&mut self, shader: &Rc<ShaderDefinition>) -> &mut ShaderEntry<K, P> {
+        let key = shader_key(shader);
+        // a released shader's address can be reused by a new one
+        let stale = self.entries.get(&key)
+            .is_some_and(|entry| !entry.shader.upgrade().is_some_and(|cached| Rc::ptr_eq(&cached, shader)));
+        if stale {
+            self.entries.remove(&key);
+        }
+        self.entries.entry(key).or_insert_with(|| ShaderEntry {
+            shader: Rc::downgrade(shader),
+            source: shader.source.clone(),
+            modified: shader.path.as_deref().and_then(modified_time),
+            pipelines: HashMap::new()
+        })
+    }
+}
+
+impl<K: Eq + Hash + Clone, P> Default for ShaderCache<K, P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Checks when shader files were modified for hot reloading, at most once per interval and once for all renderers,
+/// so that the file system isn't queried every frame of every window.
+pub struct ShaderFileWatcher {
+    interval: Duration,
+    last_poll: Option<Instant>,
+    generation: u64, // counts the polls
+    modified: HashMap<PathBuf, Option<SystemTime>> // read during the current poll
+}
+
+impl ShaderFileWatcher {
+
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_poll: None,
+            generation: 0,
+            modified: HashMap::new()
+        }
+    }
+
+    /// Starts a new poll once the interval has passed since the last one, forgetting the times read before.
+    ///
+    /// # Returns
+    ///
+    /// * `u64` - The number of the current poll, the caches only have to look for changes when it changed
+    pub fn poll(&mut self, now: Instant) -> u64 {
+        if self.last_poll.is_none_or(|last_poll| now.duration_since(last_poll) >= self.interval) {
+            self.last_poll = Some(now);
+            self.generation += 1;
+            self.modified.clear();
+        }
+        self.generation
+    }
+
+    /// When a file was last modified, read once per poll.
+    pub fn modified(&mut self, path: &Path) -> Option<SystemTime> {
+        *self.modified.entry(path.to_path_buf()).or_insert_with(|| modified_time(path))
+    }
+}
+
+fn shader_key(shader: &Rc<ShaderDefinition>) -> usize {
+    Rc::as_ptr(shader) as usize
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::Write;
+    use std::path::PathBuf;
+    use std::rc::Rc;
+    use std::time::{Duration, Instant, SystemTime};
+    use crate::engine::renderer::material::ShaderDefinition;
+    use crate::engine::renderer::shader_cache::{ShaderCache, ShaderChange, ShaderFileWatcher};
+
+    fn shader(source: &str, path: Option<PathBuf>) -> Rc<ShaderDefinition> {
+        Rc::new(ShaderDefinition { name: String::from("Test Shader"), source: String::from(source), path })
+    }
+
+    fn poll_changes(cache: &mut ShaderCache<&'static str, u32>, files: &mut ShaderFileWatcher) -> Vec<ShaderChange<&'static str>> {
+        files.poll(Instant::now());
+        cache.poll_changes(files)
+    }
+
+    fn write_file(path: &PathBuf, contents: &str, modified: SystemTime) {
+        let mut file = File::create(path).unwrap();
+        file.write_all(contents.as_bytes()).unwrap();
+        file.set_modified(modified).unwrap();
+    }
+
+    #[test]
+    fn cache_should_remember_failed_variants_and_drop_released_shaders() {
+        // given
+        let mut cache: ShaderCache<&str, u32> = ShaderCache::new();
+        let shader = shader("fn main() {}", None);
+
+        // when
+        cache.insert(&shader, "srgb", Some(1));
+        cache.insert(&shader, "linear", None);
+
+        // then
+        assert_eq!(cache.get(&shader, &"srgb"), Some(&1));
+        assert_eq!(cache.get(&shader, &"linear"), None);
+        assert!(cache.contains(&shader, &"linear"));
+        assert_eq!(cache.source(&shader), "fn main() {}");
+
+        // when
+        drop(shader);
+        cache.collect();
+
+        // then
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn poll_changes_should_report_a_changed_file_once() {
+        // given
+        let path = std::env::temp_dir().join(format!("shader_cache_test_{}.wgsl", std::process::id()));
+        let loaded_at = SystemTime::now() - Duration::from_secs(60);
+        write_file(&path, "old", loaded_at);
+        let shader = shader("old", Some(path.clone()));
+        let mut cache: ShaderCache<&str, u32> = ShaderCache::new();
+        let mut files = ShaderFileWatcher::new(Duration::ZERO);
+        cache.insert(&shader, "srgb", Some(1));
+        cache.insert(&shader, "linear", None);
+        assert!(poll_changes(&mut cache, &mut files).is_empty());
+
+        // when
+        write_file(&path, "new", loaded_at + Duration::from_secs(30));
+        let changes = poll_changes(&mut cache, &mut files);
+
+        // then: all variants are recompiled, including the one that failed before
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].source, "new");
+        let mut variants = changes[0].variants.clone();
+        variants.sort();
+        assert_eq!(variants, vec!["linear", "srgb"]);
+        assert!(poll_changes(&mut cache, &mut files).is_empty());
+
+        // when
+        cache.replace(&shader, changes[0].source.clone(), vec![("srgb", 2), ("linear", 3)]);
+
+        // then
+        assert_eq!(cache.source(&shader), "new");
+        assert_eq!(cache.get(&shader, &"linear"), Some(&3));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn poll_changes_should_keep_the_source_of_an_unchanged_shader() {
+        // given: a file changed, but the new source wasn't compiled
+        let path = std::env::temp_dir().join(format!("shader_cache_broken_test_{}.wgsl", std::process::id()));
+        let loaded_at = SystemTime::now() - Duration::from_secs(60);
+        write_file(&path, "working", loaded_at);
+        let shader = shader("working", Some(path.clone()));
+        let mut cache: ShaderCache<&str, u32> = ShaderCache::new();
+        let mut files = ShaderFileWatcher::new(Duration::ZERO);
+        cache.insert(&shader, "srgb", Some(1));
+        write_file(&path, "broken", loaded_at + Duration::from_secs(30));
+
+        // when
+        let changes = poll_changes(&mut cache, &mut files);
+
+        // then: the previous version is kept until a change is replaced
+        assert_eq!(changes.len(), 1);
+        assert_eq!(cache.source(&shader), "working");
+        assert_eq!(cache.get(&shader, &"srgb"), Some(&1));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn shader_file_watcher_should_read_files_once_per_interval() {
+        // given
+        let path = std::env::temp_dir().join(format!("shader_file_watcher_test_{}.wgsl", std::process::id()));
+        let loaded_at = SystemTime::now() - Duration::from_secs(60);
+        write_file(&path, "old", loaded_at);
+        let mut files = ShaderFileWatcher::new(Duration::from_millis(250));
+        let start = Instant::now();
+        let first_poll = files.poll(start);
+        assert_eq!(files.modified(&path), Some(loaded_at));
+
+        // when: the file changes before the interval has passed
+        write_file(&path, "new", loaded_at + Duration::from_secs(30));
+
+        // then: the time read before is kept until the next poll
+        assert_eq!(files.poll(start + Duration::from_millis(100)), first_poll);
+        assert_eq!(files.modified(&path), Some(loaded_at));
+        assert_eq!(files.poll(start + Duration::from_millis(250)), first_poll + 1);
+        assert_eq!(files.modified(&path), Some(loaded_at + Duration::from_secs(30)));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
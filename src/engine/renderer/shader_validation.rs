@@ -0,0 +1,357 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use naga::{AddressSpace, Binding, Handle, ImageClass, ImageDimension, Module, ScalarKind, ShaderStage, Span, Type, TypeInner};
+use naga::valid::{Capabilities, ValidationFlags, Validator};
+use crate::engine::renderer::shaders::{EffectUniforms, SpriteUniforms, POST_EFFECT_PRELUDE};
+
+/// Why a shader can't be used, found before it is handed to the GPU.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShaderError {
+    pub shader: String, // name of the shader
+    pub file: Option<PathBuf>, // the file the shader was loaded from, None for built-in shaders
+    pub location: Option<SourceLocation>, // where in the source the problem is, if it is known
+    pub kind: ShaderErrorKind,
+    pub message: String
+}
+
+/// A position in a shader's source, 1 based.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub line: u32,
+    pub column: u32
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShaderErrorKind {
+    /// The source isn't valid WGSL syntax.
+    Syntax,
+    /// The source parses, but breaks the rules of WGSL, e.g. a type mismatch.
+    Validation,
+    /// The shader expects vertex inputs or bindings that the renderer doesn't provide.
+    Interface
+}
+
+impl Display for ShaderError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.file {
+            Some(file) => write!(formatter, "{}", file.display())?,
+            None => write!(formatter, "{}", self.shader)?
+        }
+        if let Some(location) = self.location {
+            write!(formatter, ":{}:{}", location.line, location.column)?;
+        }
+        write!(formatter, ": {:?} error in shader {}: {}", self.kind, self.shader, self.message)
+    }
+}
+
+impl Error for ShaderError {}
+
+/// What the renderer provides to a kind of shader.
+#[derive(Debug, Clone)]
+pub struct ShaderInterface {
+    pub prelude: Option<&'static str>, // source the shader is appended to, like the one of post-processing effects
+    pub vertex_inputs: Vec<(u32, u32)>, // locations in the vertex buffer and the number of f32 components at each
+    pub bindings: Vec<ProvidedBinding>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProvidedBinding {
+    pub group: u32,
+    pub binding: u32,
+    pub resource: ProvidedResource
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProvidedResource {
+    /// A filterable 2D texture of floats.
+    Texture,
+    /// A filtering sampler.
+    Sampler,
+    /// A uniform buffer of the given size in bytes, None when the size differs between materials.
+    Uniform { size: Option<u64> }
+}
+
+impl ShaderInterface {
+
+    /// The interface of material shaders: the position, colour and texture coordinates of the vertices,
+    /// the main texture in group 0, the matrices of `SpriteUniforms` in group 1 and, for materials
+    /// with params, the params in group 2 as described by `MaterialParams`.
+    pub fn material(material_textures: Option<usize>) -> Self {
+        let mut bindings = vec![
+            ProvidedBinding { group: 0, binding: 0, resource: ProvidedResource::Texture },
+            ProvidedBinding { group: 0, binding: 1, resource: ProvidedResource::Sampler },
+            ProvidedBinding { group: 1, binding: 0, resource: ProvidedResource::Uniform { size: Some(size_of::<SpriteUniforms>() as u64) } }
+        ];
+        if let Some(texture_count) = material_textures {
+            bindings.push(ProvidedBinding { group: 2, binding: 0, resource: ProvidedResource::Uniform { size: None } });
+            for index in 0..texture_count as u32 {
+                bindings.push(ProvidedBinding { group: 2, binding: 1 + index * 2, resource: ProvidedResource::Texture });
+                bindings.push(ProvidedBinding { group: 2, binding: 2 + index * 2, resource: ProvidedResource::Sampler });
+            }
+        }
+        Self {
+            prelude: None,
+            vertex_inputs: vec![(0, 3), (1, 4), (2, 2)],
+            bindings
+        }
+    }
+
+    /// The interface of post-processing effects, which only provide a fragment shader for `POST_EFFECT_PRELUDE`.
+    pub fn effect() -> Self {
+        Self {
+            prelude: Some(POST_EFFECT_PRELUDE),
+            vertex_inputs: Vec::new(),
+            bindings: vec![
+                ProvidedBinding { group: 0, binding: 0, resource: ProvidedResource::Texture },
+                ProvidedBinding { group: 0, binding: 1, resource: ProvidedResource::Sampler },
+                ProvidedBinding { group: 1, binding: 0, resource: ProvidedResource::Uniform { size: Some(size_of::<EffectUniforms>() as u64) } },
+                ProvidedBinding { group: 2, binding: 0, resource: ProvidedResource::Texture },
+                ProvidedBinding { group: 2, binding: 1, resource: ProvidedResource::Sampler }
+            ]
+        }
+    }
+}
+
+/// What a valid shader declares, for the checks that depend on the values bound at draw time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShaderReflection {
+    pub uniform_sizes: Vec<(u32, u32, u64)> // group, binding and size in bytes of each uniform buffer
+}
+
+impl ShaderReflection {
+    pub fn uniform_size(&self, group: u32, binding: u32) -> Option<u64> {
+        self.uniform_sizes.iter()
+            .find(|(uniform_group, uniform_binding, _)| *uniform_group == group && *uniform_binding == binding)
+            .map(|(_, _, size)| *size)
+    }
+}
+
+/// Parses and validates a WGSL shader with naga and checks it against what the renderer provides,
+/// so that a broken shader is rejected with a readable error instead of failing inside wgpu.
+///
+/// # Arguments
+///
+/// * `name` - The name of the shader, for the error
+/// * `file` - The file the source was loaded from, for the error
+/// * `source` - The WGSL source, without the interface's prelude
+/// * `interface` - The vertex inputs and bindings the shader is used with
+pub fn validate_shader(name: &str, file: Option<&Path>, source: &str, interface: &ShaderInterface) -> Result<ShaderReflection, ShaderError> {
+    let (full_source, prelude_lines) = match interface.prelude {
+        Some(prelude) => (format!("{}\n{}", prelude, source), prelude.matches('\n').count() as u32 + 1),
+        None => (source.to_string(), 0)
+    };
+    // locations in the shader's own source, problems within the prelude have none
+    let locate = |location: naga::SourceLocation| (location.line_number > prelude_lines).then(|| SourceLocation {
+        line: location.line_number - prelude_lines,
+        column: location.line_position
+    });
+    let locate_span = |span: Span| span.is_defined().then(|| span.location(&full_source)).and_then(locate);
+    let error = |kind: ShaderErrorKind, location: Option<SourceLocation>, message: String| ShaderError {
+        shader: name.to_string(),
+        file: file.map(Path::to_path_buf),
+        location,
+        kind,
+        message
+    };
+
+    let module = naga::front::wgsl::parse_str(&full_source)
+        .map_err(|parse_error| error(ShaderErrorKind::Syntax, parse_error.location(&full_source).and_then(locate), parse_error.message().to_string()))?;
+    Validator::new(ValidationFlags::all(), Capabilities::default())
+        .validate(&module)
+        .map_err(|validation_error| {
+            // the causes are nested, e.g. the function, then the expression within it
+            let mut message = validation_error.as_inner().to_string();
+            let mut cause = validation_error.as_inner().source();
+            while let Some(next) = cause {
+                message.push_str(&format!(": {}", next));
+                cause = next.source();
+            }
+            error(ShaderErrorKind::Validation, validation_error.location(&full_source).and_then(locate), message)
+        })?;
+
+    check_entry_points(&module, interface).map_err(|(span, message)| error(ShaderErrorKind::Interface, span.and_then(locate_span), message))?;
+    check_bindings(&module, interface).map_err(|(span, message)| error(ShaderErrorKind::Interface, span.and_then(locate_span), message))
+}
+
+/// Checks that there is a single vertex and fragment shader, that reads only the provided vertex attributes
+/// and writes only the single colour target.
+fn check_entry_points(module: &Module, interface: &ShaderInterface) -> Result<(), (Option<Span>, String)> {
+    for stage in [ShaderStage::Vertex, ShaderStage::Fragment] {
+        let count = module.entry_points.iter().filter(|entry_point| entry_point.stage == stage).count();
+        if count != 1 {
+            return Err((None, format!("needs exactly one {:?} entry point, it has {}", stage, count)));
+        }
+    }
+
+    let vertex_shader = module.entry_points.iter().find(|entry_point| entry_point.stage == ShaderStage::Vertex).unwrap();
+    for argument in &vertex_shader.function.arguments {
+        for (location, ty) in locations(module, argument.binding.as_ref(), argument.ty) {
+            let span = Some(module.types.get_span(ty));
+            let Some((_, components)) = interface.vertex_inputs.iter().find(|(provided, _)| *provided == location) else {
+                let provided: Vec<u32> = interface.vertex_inputs.iter().map(|(provided, _)| *provided).collect();
+                return Err((span, format!("vertex input at location {} isn't in the vertex buffer, which has locations {:?}", location, provided)));
+            };
+            if float_components(&module.types[ty].inner) != Some(*components) {
+                return Err((span, format!("vertex input at location {} has to be a vec{}f", location, components)));
+            }
+        }
+    }
+
+    let fragment_shader = module.entry_points.iter().find(|entry_point| entry_point.stage == ShaderStage::Fragment).unwrap();
+    if let Some(result) = &fragment_shader.function.result {
+        if let Some((location, ty)) = locations(module, result.binding.as_ref(), result.ty).into_iter().find(|(location, _)| *location != 0) {
+            return Err((Some(module.types.get_span(ty)), format!("fragment output at location {} has no colour target, only location 0 is drawn to", location)));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that every binding the shader declares is provided with a resource of the same kind.
+fn check_bindings(module: &Module, interface: &ShaderInterface) -> Result<ShaderReflection, (Option<Span>, String)> {
+    let mut reflection = ShaderReflection::default();
+    for (handle, variable) in module.global_variables.iter() {
+        let Some(resource_binding) = &variable.binding else {
+            continue;
+        };
+        let span = Some(module.global_variables.get_span(handle));
+        let name = variable.name.as_deref().unwrap_or("unnamed");
+        let (group, binding) = (resource_binding.group, resource_binding.binding);
+        let Some(provided) = interface.bindings.iter().find(|provided| provided.group == group && provided.binding == binding) else {
+            let mut groups: Vec<u32> = interface.bindings.iter().map(|provided| provided.group).collect();
+            groups.dedup();
+            return Err((span, format!("{} is bound to group {} binding {}, which the renderer doesn't provide, it binds groups {:?}", name, group, binding, groups)));
+        };
+
+        let inner = &module.types[variable.ty].inner;
+        match provided.resource {
+            ProvidedResource::Texture => {
+                let is_float_texture = matches!(inner, TypeInner::Image {
+                    dim: ImageDimension::D2,
+                    arrayed: false,
+                    class: ImageClass::Sampled { kind: ScalarKind::Float, multi: false }
+                });
+                if !is_float_texture {
+                    return Err((span, format!("{} at group {} binding {} has to be a texture_2d<f32>", name, group, binding)));
+                }
+            },
+            ProvidedResource::Sampler => {
+                if !matches!(inner, TypeInner::Sampler { comparison: false }) {
+                    return Err((span, format!("{} at group {} binding {} has to be a sampler", name, group, binding)));
+                }
+            },
+            ProvidedResource::Uniform { size } => {
+                if variable.space != AddressSpace::Uniform {
+                    return Err((span, format!("{} at group {} binding {} has to be a var<uniform>", name, group, binding)));
+                }
+                let required = inner.size(module.to_ctx()) as u64;
+                if let Some(size) = size.filter(|size| required > *size) {
+                    return Err((span, format!("{} at group {} binding {} needs {} bytes, the renderer provides {}", name, group, binding, required, size)));
+                }
+                reflection.uniform_sizes.push((group, binding, required));
+            }
+        }
+    }
+    Ok(reflection)
+}
+
+/// The locations of an entry point argument or result, with the members of structs on their own.
+fn locations(module: &Module, binding: Option<&Binding>, ty: Handle<Type>) -> Vec<(u32, Handle<Type>)> {
+    match (binding, &module.types[ty].inner) {
+        (Some(Binding::Location { location, .. }), _) => vec![(*location, ty)],
+        (None, TypeInner::Struct { members, .. }) => members.iter()
+            .flat_map(|member| locations(module, member.binding.as_ref(), member.ty))
+            .collect(),
+        _ => Vec::new()
+    }
+}
+
+/// The number of components of an f32 scalar or vector, None for other types.
+fn float_components(inner: &TypeInner) -> Option<u32> {
+    match inner {
+        TypeInner::Scalar(scalar) if scalar.kind == ScalarKind::Float && scalar.width == 4 => Some(1),
+        TypeInner::Vector { size, scalar } if scalar.kind == ScalarKind::Float && scalar.width == 4 => Some(*size as u32),
+        _ => None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use crate::engine::renderer::shader_validation::{validate_shader, ShaderErrorKind, ShaderInterface, SourceLocation};
+
+    const SPRITE_SHADER: &str = include_str!("shaders/sprite_shader.wgsl");
+
+    #[test]
+    fn validate_shader_should_accept_the_built_in_shaders() {
+        // then
+        let reflection = validate_shader("Sprite Shader", None, SPRITE_SHADER, &ShaderInterface::material(None)).unwrap();
+        assert_eq!(reflection.uniform_size(1, 0), Some(208));
+        let effects = [
+            include_str!("shaders/post_effects/bloom_threshold.wgsl"),
+            include_str!("shaders/post_effects/blur.wgsl"),
+            include_str!("shaders/post_effects/bloom_combine.wgsl"),
+            include_str!("shaders/post_effects/vignette.wgsl"),
+            include_str!("shaders/post_effects/color_grading.wgsl"),
+            include_str!("shaders/post_effects/crt.wgsl"),
+            include_str!("shaders/post_effects/pixelate.wgsl")
+        ];
+        for effect in effects {
+            assert_eq!(validate_shader("Effect", None, effect, &ShaderInterface::effect()).map_err(|error| error.to_string()).map(|_| ()), Ok(()));
+        }
+    }
+
+    #[test]
+    fn validate_shader_should_report_syntax_errors_with_their_location() {
+        // given
+        let source = "@fragment\nfn fragment_shader() -> @location(0) vec4f {\n    return vec4f(1.0) +;\n}\n";
+
+        // when
+        let error = validate_shader("Broken", Some(Path::new("shaders/broken.wgsl")), source, &ShaderInterface::material(None)).unwrap_err();
+
+        // then
+        assert_eq!(error.kind, ShaderErrorKind::Syntax);
+        assert_eq!(error.location.map(|location| location.line), Some(3));
+        assert!(error.to_string().starts_with("shaders/broken.wgsl:3:"), "{}", error);
+    }
+
+    #[test]
+    fn validate_shader_should_locate_errors_of_effects_without_their_prelude() {
+        // given
+        let source = "@fragment\nfn fragment_shader(vertex: VertexOutput) -> @location(0) vec4f {\n    return missing;\n}\n";
+
+        // when
+        let error = validate_shader("Effect", None, source, &ShaderInterface::effect()).unwrap_err();
+
+        // then
+        assert_eq!(error.location, Some(SourceLocation { line: 3, column: 12 }));
+    }
+
+    #[test]
+    fn validate_shader_should_reject_vertex_inputs_the_renderer_does_not_provide() {
+        // given: the texture coordinates read as a vec3f
+        let source = SPRITE_SHADER.replace("@location(2) tex_coords: vec2f", "@location(2) tex_coords: vec3f")
+            .replace("vertex.tex_coords * uniforms", "vertex.tex_coords.xy * uniforms");
+
+        // when
+        let error = validate_shader("Sprite", None, &source, &ShaderInterface::material(None)).unwrap_err();
+
+        // then
+        assert_eq!(error.kind, ShaderErrorKind::Interface);
+        assert!(error.message.contains("location 2 has to be a vec2f"), "{}", error.message);
+    }
+
+    #[test]
+    fn validate_shader_should_reject_material_params_without_params() {
+        // given
+        let source = include_str!("../../sample_game/flash_shader.wgsl");
+
+        // then
+        let error = validate_shader("Flash", None, source, &ShaderInterface::material(None)).unwrap_err();
+        assert_eq!(error.kind, ShaderErrorKind::Interface);
+        assert!(error.message.contains("group 2 binding 0"), "{}", error.message);
+
+        let reflection = validate_shader("Flash", None, source, &ShaderInterface::material(Some(0))).unwrap();
+        assert_eq!(reflection.uniform_size(2, 0), Some(32));
+    }
+}
@@ -0,0 +1,26 @@
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) tex_coords: vec2f,
+}
+
+// a single triangle that covers the whole viewport, no vertex buffer needed
+@vertex
+fn vertex_shader(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let position = vec2f(f32((index << 1u) & 2u), f32(index & 2u));
+
+    var output: VertexOutput;
+    output.clip_position = vec4f(position * 2.0 - 1.0, 0.0, 1.0);
+    // render targets have their first row at the top
+    output.tex_coords = vec2f(position.x, 1.0 - position.y);
+    return output;
+}
+
+@group(0) @binding(0)
+var source_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var source_sampler: sampler;
+
+@fragment
+fn fragment_shader(vertex: VertexOutput) -> @location(0) vec4f {
+    return textureSample(source_texture, source_sampler, vertex.tex_coords);
+}
@@ -0,0 +1,12 @@
+// a single triangle that covers the whole viewport, no vertex buffer needed
+@vertex
+fn vertex_shader(@builtin(vertex_index) index: u32) -> @builtin(position) vec4f {
+    let position = vec2f(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4f(position * 2.0 - 1.0, 0.0, 1.0);
+}
+
+// the colour comes from the blend constant, so that no bind group is needed
+@fragment
+fn fragment_shader() -> @location(0) vec4f {
+    return vec4f(1.0);
+}
@@ -1,28 +1,107 @@
+use std::rc::Rc;
 use std::sync::LazyLock;
 use bytemuck::{Pod, Zeroable};
 use crate::engine::renderer::camera::Camera2D;
 use crate::engine::renderer::material::ShaderDefinition;
 use crate::engine::renderer::Renderable;
+use crate::engine::renderer::texture_region::UvRect;
 
-pub static SPRITE_SHADER: LazyLock<ShaderDefinition> = LazyLock::new(|| ShaderDefinition {
-    name: String::from("Sprite Shader"),
-    source: String::from(include_str!("sprite_shader.wgsl"))
+thread_local! {
+    static SPRITE_SHADER: Rc<ShaderDefinition> = built_in("Sprite Shader", include_str!("sprite_shader.wgsl"));
+    static BLOOM_THRESHOLD_EFFECT: Rc<ShaderDefinition> = built_in("Bloom Threshold Effect", include_str!("post_effects/bloom_threshold.wgsl"));
+    static BLUR_EFFECT: Rc<ShaderDefinition> = built_in("Blur Effect", include_str!("post_effects/blur.wgsl"));
+    static BLOOM_COMBINE_EFFECT: Rc<ShaderDefinition> = built_in("Bloom Combine Effect", include_str!("post_effects/bloom_combine.wgsl"));
+    static VIGNETTE_EFFECT: Rc<ShaderDefinition> = built_in("Vignette Effect", include_str!("post_effects/vignette.wgsl"));
+    static COLOR_GRADING_EFFECT: Rc<ShaderDefinition> = built_in("Color Grading Effect", include_str!("post_effects/color_grading.wgsl"));
+    static CRT_EFFECT: Rc<ShaderDefinition> = built_in("CRT Effect", include_str!("post_effects/crt.wgsl"));
+    static PIXELATE_EFFECT: Rc<ShaderDefinition> = built_in("Pixelate Effect", include_str!("post_effects/pixelate.wgsl"));
+}
+
+/// Compiled into the binary, so they are never reloaded.
+fn built_in(name: &str, source: &str) -> Rc<ShaderDefinition> {
+    Rc::new(ShaderDefinition {
+        name: String::from(name),
+        source: String::from(source),
+        path: None
+    })
+}
+
+/// Draws a textured mesh tinted by its vertex colours, the shader of most materials.
+pub fn sprite_shader() -> Rc<ShaderDefinition> {
+    SPRITE_SHADER.with(Rc::clone)
+}
+
+/// Copies a texture onto the viewport, e.g. to upscale a low resolution render target.
+pub static BLIT_SHADER: LazyLock<ShaderDefinition> = LazyLock::new(|| ShaderDefinition {
+    name: String::from("Blit Shader"),
+    source: String::from(include_str!("blit_shader.wgsl")),
+    path: None
+});
+
+/// Fills the scissor rect with the blend constant, e.g. to clear a single camera's viewport.
+pub static CLEAR_SHADER: LazyLock<ShaderDefinition> = LazyLock::new(|| ShaderDefinition {
+    name: String::from("Clear Shader"),
+    source: String::from(include_str!("clear_shader.wgsl")),
+    path: None
 });
 
+/// Declares the fullscreen vertex shader and the bindings of post-processing effects.
+/// Effect shaders only contain the fragment shader and are appended to it.
+pub const POST_EFFECT_PRELUDE: &str = include_str!("post_effects/prelude.wgsl");
+
+pub fn bloom_threshold_effect() -> Rc<ShaderDefinition> {
+    BLOOM_THRESHOLD_EFFECT.with(Rc::clone)
+}
+
+pub fn blur_effect() -> Rc<ShaderDefinition> {
+    BLUR_EFFECT.with(Rc::clone)
+}
+
+pub fn bloom_combine_effect() -> Rc<ShaderDefinition> {
+    BLOOM_COMBINE_EFFECT.with(Rc::clone)
+}
+
+pub fn vignette_effect() -> Rc<ShaderDefinition> {
+    VIGNETTE_EFFECT.with(Rc::clone)
+}
+
+pub fn color_grading_effect() -> Rc<ShaderDefinition> {
+    COLOR_GRADING_EFFECT.with(Rc::clone)
+}
+
+pub fn crt_effect() -> Rc<ShaderDefinition> {
+    CRT_EFFECT.with(Rc::clone)
+}
+
+pub fn pixelate_effect() -> Rc<ShaderDefinition> {
+    PIXELATE_EFFECT.with(Rc::clone)
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, Pod, Zeroable)]
 pub struct SpriteUniforms {
     pub local_to_world_model_matrix: [[f32; 4]; 4],
     pub world_to_view_matrix: [[f32; 4]; 4],
-    pub view_to_clip_matrix: [[f32; 4]; 4]
+    pub view_to_clip_matrix: [[f32; 4]; 4],
+    pub uv_rect: [f32; 4] // offset and size of the drawn texture region
 }
 
 impl SpriteUniforms {
     pub fn new(renderable: &Renderable, camera: &Camera2D) -> Self {
+        let uv_rect = renderable.material.region.map_or(UvRect::FULL, |region| region.uv_rect());
         Self {
-            local_to_world_model_matrix: renderable.transform.local_to_world_model_matrix().to_cols_array_2d(),
+            local_to_world_model_matrix: renderable.model_matrix().to_cols_array_2d(),
             world_to_view_matrix: camera.world_to_view_matrix().to_cols_array_2d(),
-            view_to_clip_matrix: camera.view_to_clip_matrix().to_cols_array_2d()
+            view_to_clip_matrix: camera.view_to_clip_matrix().to_cols_array_2d(),
+            uv_rect: [uv_rect.x, uv_rect.y, uv_rect.width, uv_rect.height]
         }
     }
-}
\ No newline at end of file
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+pub struct EffectUniforms {
+    pub params: [f32; 4], // meaning depends on the effect
+    pub resolution: [f32; 2], // pixels of the target drawn to
+    pub texel_size: [f32; 2] // 1 / pixels of the source texture
+}
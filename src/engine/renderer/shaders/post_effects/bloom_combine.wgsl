@@ -0,0 +1,7 @@
+// adds the blurred glow in the extra texture, scaled by params.x
+@fragment
+fn fragment_shader(vertex: VertexOutput) -> @location(0) vec4f {
+    let color = textureSample(source_texture, source_sampler, vertex.tex_coords);
+    let glow = textureSample(extra_texture, extra_sampler, vertex.tex_coords).rgb;
+    return vec4f(color.rgb + glow * effect_uniforms.params.x, color.a);
+}
@@ -0,0 +1,8 @@
+// keeps the parts brighter than params.x
+@fragment
+fn fragment_shader(vertex: VertexOutput) -> @location(0) vec4f {
+    let color = textureSample(source_texture, source_sampler, vertex.tex_coords);
+    let brightness = max(color.r, max(color.g, color.b));
+    let contribution = max(brightness - effect_uniforms.params.x, 0.0) / max(brightness, 0.0001);
+    return vec4f(color.rgb * contribution, 1.0);
+}
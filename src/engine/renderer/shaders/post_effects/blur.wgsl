@@ -0,0 +1,14 @@
+// gaussian blur along params.yz, with params.x scaling the distance between the samples
+@fragment
+fn fragment_shader(vertex: VertexOutput) -> @location(0) vec4f {
+    var weights = array<f32, 5>(0.227027, 0.1945946, 0.1216216, 0.054054, 0.016216);
+    let step = effect_uniforms.params.yz * effect_uniforms.texel_size * effect_uniforms.params.x;
+
+    var color = textureSample(source_texture, source_sampler, vertex.tex_coords).rgb * weights[0];
+    for (var i = 1; i < 5; i++) {
+        let offset = step * f32(i);
+        color += textureSample(source_texture, source_sampler, vertex.tex_coords + offset).rgb * weights[i];
+        color += textureSample(source_texture, source_sampler, vertex.tex_coords - offset).rgb * weights[i];
+    }
+    return vec4f(color, 1.0);
+}
@@ -0,0 +1,29 @@
+// looks the colours up in the LUT in the extra texture, blended in by params.x
+
+fn linear_to_srgb(color: vec3f) -> vec3f {
+    return select(1.055 * pow(color, vec3f(1.0 / 2.4)) - 0.055, color * 12.92, color <= vec3f(0.0031308));
+}
+
+fn srgb_to_linear(color: vec3f) -> vec3f {
+    return select(pow((color + 0.055) / 1.055, vec3f(2.4)), color / 12.92, color <= vec3f(0.04045));
+}
+
+@fragment
+fn fragment_shader(vertex: VertexOutput) -> @location(0) vec4f {
+    let color = textureSample(source_texture, source_sampler, vertex.tex_coords);
+    let size = f32(textureDimensions(extra_texture).y);
+
+    // LUTs are made for the colours as they are shown, not for linear ones
+    let shown = clamp(linear_to_srgb(color.rgb), vec3f(0.0), vec3f(1.0)) * (size - 1.0);
+    let slice = floor(shown.b);
+    // red to the right within a slice of blue, green downwards, with the texture stored bottom row first
+    let uv = vec2f(
+        (slice * size + shown.r + 0.5) / (size * size),
+        1.0 - (shown.g + 0.5) / size
+    );
+    let lower = textureSample(extra_texture, extra_sampler, uv).rgb;
+    let upper = textureSample(extra_texture, extra_sampler, uv + vec2f(1.0 / size, 0.0)).rgb;
+    let graded = srgb_to_linear(mix(lower, upper, shown.b - slice));
+
+    return vec4f(mix(color.rgb, graded, effect_uniforms.params.x), color.a);
+}
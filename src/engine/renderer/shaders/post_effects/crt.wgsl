@@ -0,0 +1,13 @@
+// scanlines as dark as params.x on a screen curved by params.y
+@fragment
+fn fragment_shader(vertex: VertexOutput) -> @location(0) vec4f {
+    let centered = vertex.tex_coords * 2.0 - 1.0;
+    let curved = centered + centered * centered.yx * centered.yx * effect_uniforms.params.y;
+    let uv = curved * 0.5 + 0.5;
+
+    let color = textureSample(source_texture, source_sampler, uv).rgb;
+    // every other line of the target is darkened
+    let scanline = 1.0 - effect_uniforms.params.x * (0.5 + 0.5 * sin(uv.y * effect_uniforms.resolution.y * 3.1415927));
+    let on_screen = all(uv >= vec2f(0.0)) && all(uv <= vec2f(1.0));
+    return select(vec4f(0.0, 0.0, 0.0, 1.0), vec4f(color * scanline, 1.0), on_screen);
+}
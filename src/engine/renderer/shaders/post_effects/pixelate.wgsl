@@ -0,0 +1,7 @@
+// samples the center of blocks of params.x pixels
+@fragment
+fn fragment_shader(vertex: VertexOutput) -> @location(0) vec4f {
+    let block = max(effect_uniforms.params.x, 1.0) / effect_uniforms.resolution;
+    let uv = (floor(vertex.tex_coords / block) + 0.5) * block;
+    return textureSample(source_texture, source_sampler, uv);
+}
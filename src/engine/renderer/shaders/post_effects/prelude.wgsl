@@ -0,0 +1,38 @@
+// shared by all post-processing effects, which only provide the fragment shader
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) tex_coords: vec2f,
+}
+
+struct EffectUniforms {
+    params: vec4f, // meaning depends on the effect
+    resolution: vec2f, // pixels of the target drawn to
+    texel_size: vec2f, // 1 / pixels of the source texture
+}
+
+// the frame drawn so far
+@group(0) @binding(0)
+var source_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var source_sampler: sampler;
+
+@group(1) @binding(0)
+var<uniform> effect_uniforms: EffectUniforms;
+
+// a second texture, e.g. a lookup table, the source texture when the effect has none
+@group(2) @binding(0)
+var extra_texture: texture_2d<f32>;
+@group(2) @binding(1)
+var extra_sampler: sampler;
+
+// a single triangle that covers the whole target, no vertex buffer needed
+@vertex
+fn vertex_shader(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let position = vec2f(f32((index << 1u) & 2u), f32(index & 2u));
+
+    var output: VertexOutput;
+    output.clip_position = vec4f(position * 2.0 - 1.0, 0.0, 1.0);
+    // render targets have their first row at the top
+    output.tex_coords = vec2f(position.x, 1.0 - position.y);
+    return output;
+}
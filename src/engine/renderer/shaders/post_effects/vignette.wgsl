@@ -0,0 +1,9 @@
+// darkens the corners by params.x, starting params.y away from them
+@fragment
+fn fragment_shader(vertex: VertexOutput) -> @location(0) vec4f {
+    let color = textureSample(source_texture, source_sampler, vertex.tex_coords);
+    // 0 in the center, 1 in the corners
+    let distance = length(vertex.tex_coords - vec2f(0.5)) * 1.4142135;
+    let falloff = smoothstep(1.0 - effect_uniforms.params.y, 1.0, distance);
+    return vec4f(color.rgb * (1.0 - falloff * effect_uniforms.params.x), color.a);
+}
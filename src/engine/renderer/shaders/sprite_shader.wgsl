@@ -13,7 +13,8 @@ struct VertexOutput {
 struct Uniforms {
     local_to_world_model_matrix: mat4x4<f32>,
     world_to_view_matrix: mat4x4<f32>,
-    view_to_clip_matrix: mat4x4<f32>
+    view_to_clip_matrix: mat4x4<f32>,
+    uv_rect: vec4f // offset in xy and size in zw
 }
 
 @group(1) @binding(0)
@@ -27,7 +28,8 @@ fn vertex_shader(vertex: VertexData) -> VertexOutput {
     var output: VertexOutput;
     output.clip_position = uniforms.view_to_clip_matrix * view_space;
     output.color = vertex.color;
-    output.tex_coords = vertex.tex_coords;
+    // map the whole mesh onto the texture region
+    output.tex_coords = uniforms.uv_rect.xy + vertex.tex_coords * uniforms.uv_rect.zw;
     return output;
 }
 
@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+use crate::engine::renderer::material::Texture;
+use crate::engine::renderer::mipmap::mip_level_count;
+
+/// GPU resources of textures, created when a texture is first drawn and kept while it is in use.
+///
+/// Entries are keyed by the identity of the shared texture rather than its name, so that two different
+/// textures with the same name don't replace each other. An entry is released once the last handle to its
+/// texture is dropped, and when a memory budget is set, the least recently drawn textures are evicted to
+/// stay within it. Evicted textures that are drawn again are simply uploaded again.
+pub struct TextureCache<T> {
+    entries: HashMap<TextureKey, TextureCacheEntry<T>>,
+    memory_budget: Option<u64>, // bytes, unlimited when None
+    memory_used: u64 // bytes
+}
+
+/// The address of a shared texture, which can't be reused while the entry holds a weak handle to it.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+struct TextureKey(usize);
+
+struct TextureCacheEntry<T> {
+    texture: Weak<Texture>,
+    name: String,
+    bytes: u64,
+    last_used_frame: u64,
+    dynamic: bool, // updated on the GPU, so it can't be uploaded again from the texture's data
+    resource: T
+}
+
+/// A texture whose GPU resources were freed.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TextureEviction {
+    pub name: String,
+    pub bytes: u64,
+    pub reason: EvictionReason
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum EvictionReason {
+    /// The last handle to the texture was dropped.
+    Released,
+    /// The memory budget was exceeded and the texture wasn't drawn in the current frame.
+    OverBudget
+}
+
+impl<T> TextureCache<T> {
+
+    pub fn new(memory_budget: Option<u64>) -> Self {
+        Self {
+            entries: HashMap::new(),
+            memory_budget,
+            memory_used: 0
+        }
+    }
+
+    pub fn set_memory_budget(&mut self, memory_budget: Option<u64>) {
+        self.memory_budget = memory_budget;
+    }
+
+    /// The estimated GPU memory of all cached textures in bytes.
+    pub fn memory_used(&self) -> u64 {
+        self.memory_used
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains(&self, texture: &Rc<Texture>) -> bool {
+        self.entries.contains_key(&TextureKey::of(texture))
+    }
+
+    /// Returns the resource of a texture, created with `create` if the texture isn't cached yet,
+    /// and marks it as used in the given frame.
+    pub fn get_or_insert_with(&mut self, texture: &Rc<Texture>, frame: u64, create: impl FnOnce() -> T) -> &T {
+        let entry = self.entries.entry(TextureKey::of(texture)).or_insert_with(|| {
+            let bytes = texture_bytes(texture);
+            self.memory_used += bytes;
+            TextureCacheEntry {
+                texture: Rc::downgrade(texture),
+                name: texture.name.clone(),
+                bytes,
+                last_used_frame: frame,
+                dynamic: false,
+                resource: create()
+            }
+        });
+        entry.last_used_frame = frame;
+        &entry.resource
+    }
+
+    pub fn get(&self, texture: &Rc<Texture>) -> Option<&T> {
+        self.entries.get(&TextureKey::of(texture)).map(|entry| &entry.resource)
+    }
+
+    /// Keeps a texture from being evicted over budget, because its GPU copy was changed and would be lost.
+    /// It is still released when its last handle is dropped.
+    pub fn mark_dynamic(&mut self, texture: &Rc<Texture>) {
+        if let Some(entry) = self.entries.get_mut(&TextureKey::of(texture)) {
+            entry.dynamic = true;
+        }
+    }
+
+    /// Frees the resource of a texture right away, e.g. when a level is unloaded while its textures are still
+    /// referenced. Returns whether the texture was cached.
+    pub fn unload(&mut self, texture: &Rc<Texture>) -> bool {
+        match self.entries.remove(&TextureKey::of(texture)) {
+            Some(entry) => {
+                self.memory_used -= entry.bytes;
+                true
+            },
+            None => false
+        }
+    }
+
+    /// Frees the resources of released textures, then evicts the least recently used ones until the cache
+    /// fits its memory budget. Textures used in the current frame are never evicted, even over budget.
+    pub fn collect(&mut self, current_frame: u64) -> Vec<TextureEviction> {
+        let mut evictions = Vec::new();
+
+        let released: Vec<TextureKey> = self.entries.iter()
+            .filter(|(_, entry)| entry.texture.strong_count() == 0)
+            .map(|(key, _)| *key)
+            .collect();
+        for key in released {
+            evictions.push(self.evict(key, EvictionReason::Released));
+        }
+
+        if let Some(memory_budget) = self.memory_budget {
+            let mut unused: Vec<(TextureKey, u64)> = self.entries.iter()
+                .filter(|(_, entry)| entry.last_used_frame < current_frame && !entry.dynamic)
+                .map(|(key, entry)| (*key, entry.last_used_frame))
+                .collect();
+            unused.sort_by_key(|(_, last_used_frame)| *last_used_frame);
+            for (key, _) in unused {
+                if self.memory_used <= memory_budget {
+                    break;
+                }
+                evictions.push(self.evict(key, EvictionReason::OverBudget));
+            }
+        }
+        evictions
+    }
+
+    fn evict(&mut self, key: TextureKey, reason: EvictionReason) -> TextureEviction {
+        let entry = self.entries.remove(&key).unwrap();
+        self.memory_used -= entry.bytes;
+        TextureEviction {
+            name: entry.name,
+            bytes: entry.bytes,
+            reason
+        }
+    }
+}
+
+impl TextureKey {
+    fn of(texture: &Rc<Texture>) -> Self {
+        Self(Rc::as_ptr(texture) as usize)
+    }
+}
+
+/// The GPU memory of an RGBA texture, including its mipmaps.
+fn texture_bytes(texture: &Texture) -> u64 {
+    let levels = if texture.mipmaps { mip_level_count(texture.width, texture.height) } else { 1 };
+    (0..levels)
+        .map(|level| (texture.width >> level).max(1) as u64 * (texture.height >> level).max(1) as u64 * 4)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use crate::engine::renderer::material::Texture;
+    use crate::engine::renderer::texture_cache::{EvictionReason, TextureCache, TextureEviction};
+
+    #[test]
+    fn texture_cache_should_keep_textures_with_the_same_name_apart() {
+        // given
+        let mut cache = TextureCache::new(None);
+        let grass = Rc::new(Texture::filled("tile", 2, 2, 0));
+        let water = Rc::new(Texture::filled("tile", 2, 2, 0));
+
+        // when
+        let grass_resource = *cache.get_or_insert_with(&grass, 0, || 1);
+        let water_resource = *cache.get_or_insert_with(&water, 0, || 2);
+        let cached_grass_resource = *cache.get_or_insert_with(&grass, 1, || 3);
+
+        // then
+        assert_eq!([grass_resource, water_resource, cached_grass_resource], [1, 2, 1]);
+        assert_eq!(cache.memory_used(), 2 * 16);
+    }
+
+    #[test]
+    fn texture_cache_should_release_textures_without_handles() {
+        // given
+        let mut cache = TextureCache::new(None);
+        let kept = Rc::new(Texture::filled("kept", 2, 2, 0));
+        let dropped = Rc::new(Texture::filled("dropped", 2, 2, 0));
+        cache.get_or_insert_with(&kept, 0, || ());
+        cache.get_or_insert_with(&dropped, 0, || ());
+
+        // when
+        drop(dropped);
+        let evictions = cache.collect(1);
+
+        // then
+        assert_eq!(evictions, vec![TextureEviction { name: String::from("dropped"), bytes: 16, reason: EvictionReason::Released }]);
+        assert!(cache.contains(&kept));
+        assert_eq!(cache.memory_used(), 16);
+    }
+
+    #[test]
+    fn texture_cache_should_evict_least_recently_used_textures_over_budget() {
+        // given: a budget for two of the three textures
+        let mut cache = TextureCache::new(Some(2 * 64));
+        let textures = ["old", "recent", "current"].map(|name| Rc::new(Texture::filled(name, 4, 4, 0)));
+        for (frame, texture) in textures.iter().enumerate() {
+            cache.get_or_insert_with(texture, frame as u64, || ());
+        }
+
+        // when
+        let evictions = cache.collect(2);
+
+        // then
+        assert_eq!(evictions, vec![TextureEviction { name: String::from("old"), bytes: 64, reason: EvictionReason::OverBudget }]);
+        assert!(cache.contains(&textures[1]));
+        assert!(cache.contains(&textures[2]));
+    }
+
+    #[test]
+    fn texture_cache_should_not_evict_dynamic_textures_over_budget() {
+        // given: an old fog of war mask that was updated on the GPU
+        let mut cache = TextureCache::new(Some(64));
+        let fog = Rc::new(Texture::filled("fog", 4, 4, 0));
+        let current = Rc::new(Texture::filled("current", 4, 4, 0));
+        cache.get_or_insert_with(&fog, 0, || ());
+        cache.mark_dynamic(&fog);
+        cache.get_or_insert_with(&current, 1, || ());
+
+        // when
+        let evictions = cache.collect(1);
+
+        // then
+        assert!(evictions.is_empty());
+        assert!(cache.contains(&fog));
+    }
+
+    #[test]
+    fn texture_cache_should_unload_explicitly() {
+        // given
+        let mut cache = TextureCache::new(None);
+        let texture = Rc::new(Texture::filled("level", 2, 2, 0));
+        cache.get_or_insert_with(&texture, 0, || ());
+
+        // then
+        assert!(cache.unload(&texture));
+        assert!(!cache.unload(&texture));
+        assert_eq!(cache.memory_used(), 0);
+    }
+}
@@ -0,0 +1,104 @@
+use crate::engine::renderer::material::Texture;
+
+/// A rectangle in texture coordinates from 0 to 1, with the origin at the lower left like the stored textures.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UvRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32
+}
+
+impl UvRect {
+    pub const FULL: UvRect = UvRect { x: 0.0, y: 0.0, width: 1.0, height: 1.0 };
+}
+
+/// A rectangle inside a texture, e.g. a single frame of a sprite sheet or an image in an atlas.
+/// Pixel coordinates have their origin at the top left, as in image editors and atlas files.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct TextureRegion {
+    pub x: u32, // pixels
+    pub y: u32, // pixels
+    pub width: u32, // pixels
+    pub height: u32, // pixels
+    pub texture_size: [u32; 2] // pixels, the size of the whole texture
+}
+
+impl TextureRegion {
+
+    /// The region covering the whole texture.
+    pub fn full(texture: &Texture) -> Self {
+        Self {
+            x: 0,
+            y: 0,
+            width: texture.width,
+            height: texture.height,
+            texture_size: [texture.width, texture.height]
+        }
+    }
+
+    pub fn uv_rect(&self) -> UvRect {
+        let [texture_width, texture_height] = self.texture_size.map(|size| size.max(1) as f32);
+        UvRect {
+            x: self.x as f32 / texture_width,
+            // textures are stored bottom row first
+            y: (self.texture_size[1].saturating_sub(self.y + self.height)) as f32 / texture_height,
+            width: self.width as f32 / texture_width,
+            height: self.height as f32 / texture_height
+        }
+    }
+}
+
+/// Slices a sprite sheet into cells of equal size, row by row from the top left.
+/// Cells that don't fit completely at the right and bottom edges are left out.
+pub fn slice_grid(texture: &Texture, cell_size: [u32; 2]) -> Vec<TextureRegion> {
+    let [cell_width, cell_height] = cell_size;
+    if cell_width == 0 || cell_height == 0 {
+        return Vec::new();
+    }
+
+    let columns = texture.width / cell_width;
+    let rows = texture.height / cell_height;
+    (0..rows)
+        .flat_map(|row| (0..columns).map(move |column| (row, column)))
+        .map(|(row, column)| TextureRegion {
+            x: column * cell_width,
+            y: row * cell_height,
+            width: cell_width,
+            height: cell_height,
+            texture_size: [texture.width, texture.height]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::renderer::material::Texture;
+    use crate::engine::renderer::texture_region::{slice_grid, TextureRegion, UvRect};
+
+    #[test]
+    fn texture_region_should_flip_to_texture_coordinates() {
+        // given: the top right quarter of the texture
+        let region = TextureRegion { x: 50, y: 0, width: 50, height: 25, texture_size: [100, 100] };
+
+        // when
+        let uv_rect = region.uv_rect();
+
+        // then
+        assert_eq!(uv_rect, UvRect { x: 0.5, y: 0.75, width: 0.5, height: 0.25 });
+    }
+
+    #[test]
+    fn slice_grid_should_return_whole_cells_row_by_row() {
+        // given: 3 columns and 2 rows, with a leftover strip at the right
+        let sheet = Texture::filled("Sheet", 100, 64, 0);
+
+        // when
+        let regions = slice_grid(&sheet, [32, 32]);
+
+        // then
+        assert_eq!(regions.len(), 6);
+        assert_eq!(regions[1], TextureRegion { x: 32, y: 0, width: 32, height: 32, texture_size: [100, 64] });
+        assert_eq!(regions[3], TextureRegion { x: 0, y: 32, width: 32, height: 32, texture_size: [100, 64] });
+    }
+}
@@ -1,22 +1,42 @@
-use glam::{Mat4, Vec3};
+use glam::{Mat4, Vec3, Vec4};
 
 #[derive(Debug, Clone)]
 pub struct Transform2D {
-    pub position: [f32; 2],
-    pub scale: [f32; 2]
+    pub position: [f32; 2], // where the pivot ends up, in world units or relative to the parent
+    pub scale: [f32; 2],
+    pub rotation: f32, // radians, counter-clockwise around the pivot
+    pub pivot: [f32; 2] // 0 - 1 within the mesh, [0, 0] is the lower left corner and [0.5, 0.5] the center
+}
+
+impl Default for Transform2D {
+    fn default() -> Self {
+        Self {
+            position: [0.0, 0.0],
+            scale: [1.0, 1.0],
+            rotation: 0.0,
+            pivot: [0.0, 0.0]
+        }
+    }
 }
 
 impl Transform2D {
     pub fn local_to_world_model_matrix(&self) -> Mat4 {
-        // from local (-0.5 - 0.5) to local (0 - 1)
-        let local_to_world_local = Mat4::from_translation(Vec3::new(0.5, 0.5, 0.0));
-        
-        // now scale and move within world space
+        // from local (-0.5 - 0.5) to local (0 - 1), then move the pivot to the origin
+        let local_to_pivot = Mat4::from_translation(Vec3::new(0.5 - self.pivot[0], 0.5 - self.pivot[1], 0.0));
+
+        // now scale, rotate around the pivot and move within world space
         let scale_transform = Mat4::from_scale(Vec3::new(self.scale[0], self.scale[1], 1.0));
+        let rotation_transform = Mat4::from_rotation_z(self.rotation);
         let position_transform = Mat4::from_translation(Vec3::new(self.position[0], self.position[1], 0.0));
 
         // executed right to left
-        position_transform * scale_transform * local_to_world_local
+        position_transform * rotation_transform * scale_transform * local_to_pivot
+    }
+
+    /// The world position of the center of the local space.
+    pub fn center(&self) -> [f32; 2] {
+        let center = self.local_to_world_model_matrix() * Vec4::new(0.0, 0.0, 0.0, 1.0);
+        [center.x, center.y]
     }
 
     pub fn position_to(&self, x_offset: f32, y_offset: f32) -> Self {
@@ -30,7 +50,8 @@ impl Transform2D {
 
 #[cfg(test)]
 mod tests {
-    use glam::Vec4;
+    use std::f32::consts::FRAC_PI_2;
+    use glam::{Vec2, Vec4};
     use crate::engine::renderer::transform::Transform2D;
 
     #[test]
@@ -38,7 +59,8 @@ mod tests {
         // given
         let transform = Transform2D {
             position: [100.0, 100.0],
-            scale: [2.0, 2.0]
+            scale: [2.0, 2.0],
+            ..Transform2D::default()
         };
         let world_space_matrix = transform.local_to_world_model_matrix();
         
@@ -55,4 +77,23 @@ mod tests {
         // then
         assert_eq!(world_space, Vec4::new(101.0, 101.0, 0.0, 1.0));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn transform2d_should_rotate_around_pivot() {
+        // given: a 2x2 square rotated a quarter turn around its center at (10, 10)
+        let transform = Transform2D {
+            position: [10.0, 10.0],
+            scale: [2.0, 2.0],
+            rotation: FRAC_PI_2,
+            pivot: [0.5, 0.5]
+        };
+        let world_space_matrix = transform.local_to_world_model_matrix();
+
+        // when: lower right corner in local space
+        let world_space = world_space_matrix * Vec4::new(0.5, -0.5, 0.0, 1.0);
+
+        // then: the corner turned to the upper right, the center stayed in place
+        assert!(Vec2::new(world_space.x, world_space.y).abs_diff_eq(Vec2::new(11.0, 11.0), 1e-5));
+        assert!(Vec2::from(transform.center()).abs_diff_eq(Vec2::new(10.0, 10.0), 1e-5));
+    }
+}
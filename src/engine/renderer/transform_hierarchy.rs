@@ -0,0 +1,188 @@
+use std::cell::Cell;
+use glam::Mat4;
+use crate::engine::renderer::transform::Transform2D;
+
+/// Identifies a transform within its `TransformHierarchy`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct TransformId(usize);
+
+/// Transforms that can be attached to each other, e.g. a weapon to a hand or a moon to a planet.
+/// A child's transform is relative to the local space of its parent, in which the parent's mesh spans
+/// -0.5 to 0.5, so the child moves, rotates and scales along with its parent.
+///
+/// World matrices are computed when they are asked for, and cached until the transform
+/// or one of its ancestors changes.
+#[derive(Debug, Default)]
+pub struct TransformHierarchy {
+    nodes: Vec<TransformNode>
+}
+
+#[derive(Debug)]
+struct TransformNode {
+    local: Transform2D,
+    parent: Option<TransformId>,
+    children: Vec<TransformId>,
+    world_matrix: Cell<Mat4>,
+    dirty: Cell<bool>
+}
+
+impl TransformHierarchy {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a transform, at the root of the hierarchy when it has no parent.
+    pub fn add(&mut self, local: Transform2D, parent: Option<TransformId>) -> TransformId {
+        let id = TransformId(self.nodes.len());
+        self.nodes.push(TransformNode {
+            local,
+            parent,
+            children: Vec::new(),
+            world_matrix: Cell::new(Mat4::IDENTITY),
+            dirty: Cell::new(true)
+        });
+        if let Some(parent) = parent {
+            self.nodes[parent.0].children.push(id);
+        }
+        id
+    }
+
+    pub fn local(&self, id: TransformId) -> &Transform2D {
+        &self.nodes[id.0].local
+    }
+
+    pub fn set_local(&mut self, id: TransformId, local: Transform2D) {
+        self.nodes[id.0].local = local;
+        self.mark_dirty(id);
+    }
+
+    pub fn parent(&self, id: TransformId) -> Option<TransformId> {
+        self.nodes[id.0].parent
+    }
+
+    /// Attaches a transform to another parent, or detaches it when the parent is `None`.
+    /// Its local transform is kept, so it jumps to the same place relative to the new parent.
+    pub fn set_parent(&mut self, id: TransformId, parent: Option<TransformId>) -> Result<(), String> {
+        if parent.is_some_and(|parent| self.is_ancestor_or_self(id, parent)) {
+            return Err(format!("Transform {:?} can't become a child of its own descendant {:?}", id, parent));
+        }
+
+        if let Some(old_parent) = self.nodes[id.0].parent {
+            self.nodes[old_parent.0].children.retain(|child| *child != id);
+        }
+        if let Some(new_parent) = parent {
+            self.nodes[new_parent.0].children.push(id);
+        }
+        self.nodes[id.0].parent = parent;
+        self.mark_dirty(id);
+        Ok(())
+    }
+
+    /// The matrix from the local space of the transform's mesh to world space, through all its ancestors.
+    pub fn world_matrix(&self, id: TransformId) -> Mat4 {
+        let node = &self.nodes[id.0];
+        if node.dirty.get() {
+            let parent_matrix = node.parent.map_or(Mat4::IDENTITY, |parent| self.world_matrix(parent));
+            node.world_matrix.set(parent_matrix * node.local.local_to_world_model_matrix());
+            node.dirty.set(false);
+        }
+        node.world_matrix.get()
+    }
+
+    /// Marks the cached world matrices of the transform and all its descendants as outdated.
+    fn mark_dirty(&self, id: TransformId) {
+        let mut pending = vec![id];
+        while let Some(id) = pending.pop() {
+            let node = &self.nodes[id.0];
+            // descendants of a dirty transform are dirty already, computing them requires their ancestors
+            if node.dirty.replace(true) {
+                continue;
+            }
+            pending.extend(node.children.iter().copied());
+        }
+    }
+
+    fn is_ancestor_or_self(&self, ancestor: TransformId, id: TransformId) -> bool {
+        let mut current = Some(id);
+        while let Some(id) = current {
+            if id == ancestor {
+                return true;
+            }
+            current = self.nodes[id.0].parent;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+    use glam::{Vec2, Vec4};
+    use crate::engine::renderer::transform::Transform2D;
+    use crate::engine::renderer::transform_hierarchy::{TransformHierarchy, TransformId};
+
+    fn world_center(hierarchy: &TransformHierarchy, id: TransformId) -> Vec2 {
+        let center = hierarchy.world_matrix(id) * Vec4::new(0.0, 0.0, 0.0, 1.0);
+        Vec2::new(center.x, center.y)
+    }
+
+    #[test]
+    fn transform_hierarchy_should_move_and_rotate_children_with_their_parent() {
+        // given: a moon one unit to the right of the center of its planet
+        let mut hierarchy = TransformHierarchy::new();
+        let planet = hierarchy.add(Transform2D { position: [10.0, 10.0], pivot: [0.5, 0.5], ..Transform2D::default() }, None);
+        let moon = hierarchy.add(Transform2D { position: [1.0, 0.0], pivot: [0.5, 0.5], ..Transform2D::default() }, Some(planet));
+
+        // when: the planet moves and turns a quarter
+        hierarchy.set_local(planet, Transform2D {
+            position: [20.0, 10.0],
+            rotation: FRAC_PI_2,
+            pivot: [0.5, 0.5],
+            ..Transform2D::default()
+        });
+
+        // then: the moon is now above the planet
+        assert!(world_center(&hierarchy, moon).abs_diff_eq(Vec2::new(20.0, 11.0), 1e-5));
+    }
+
+    #[test]
+    fn transform_hierarchy_should_only_recompute_changed_transforms() {
+        // given
+        let mut hierarchy = TransformHierarchy::new();
+        let body = hierarchy.add(Transform2D::default(), None);
+        let hand = hierarchy.add(Transform2D::default(), Some(body));
+        let weapon = hierarchy.add(Transform2D::default(), Some(hand));
+        let other = hierarchy.add(Transform2D::default(), None);
+        for id in [body, hand, weapon, other] {
+            hierarchy.world_matrix(id);
+        }
+
+        // when
+        hierarchy.set_local(hand, Transform2D { position: [1.0, 0.0], ..Transform2D::default() });
+
+        // then: the hand and everything attached to it are outdated, the rest is still cached
+        let dirty = |id: TransformId| hierarchy.nodes[id.0].dirty.get();
+        assert!(!dirty(body));
+        assert!(dirty(hand));
+        assert!(dirty(weapon));
+        assert!(!dirty(other));
+        assert!(world_center(&hierarchy, weapon).abs_diff_eq(Vec2::new(2.5, 1.5), 1e-5));
+        assert!(!dirty(weapon));
+    }
+
+    #[test]
+    fn transform_hierarchy_should_reject_cycles() {
+        // given
+        let mut hierarchy = TransformHierarchy::new();
+        let parent = hierarchy.add(Transform2D::default(), None);
+        let child = hierarchy.add(Transform2D::default(), Some(parent));
+
+        // when
+        let result = hierarchy.set_parent(parent, Some(child));
+
+        // then
+        assert!(result.is_err());
+        assert_eq!(hierarchy.parent(parent), None);
+    }
+}
@@ -1,2 +1,6 @@
 pub mod wgpu_renderer;
-mod wgpu_texture;
\ No newline at end of file
+mod wgpu_texture;
+mod wgpu_render_target;
+mod wgpu_sampler;
+mod wgpu_post_processing;
+mod wgpu_staging_buffer;
@@ -0,0 +1,49 @@
+use crate::engine::renderer::post_processing::PostTarget;
+use crate::engine::renderer::wgpu::wgpu_render_target::WgpuRenderTarget;
+
+/// The offscreen targets of the post-processing chain: the frame the layers draw to,
+/// the pair the effects alternate between and the half sized pair the bloom is blurred in.
+pub struct WgpuPostProcessingTargets {
+    pub scene: WgpuRenderTarget,
+    pub ping: WgpuRenderTarget,
+    pub pong: WgpuRenderTarget,
+    pub bloom: [WgpuRenderTarget; 2],
+    pub format: wgpu::TextureFormat
+}
+
+impl WgpuPostProcessingTargets {
+
+    pub fn new(
+        device: &wgpu::Device,
+        size: [u32; 2],
+        format: wgpu::TextureFormat,
+        sampler: &wgpu::Sampler,
+        bind_group_layout: &wgpu::BindGroupLayout
+    ) -> Self {
+        let target = |label: &str, size: [u32; 2]| WgpuRenderTarget::new(device, label, size, format, sampler, bind_group_layout);
+        let bloom_size = [(size[0] / 2).max(1), (size[1] / 2).max(1)];
+        Self {
+            scene: target("post_processing_scene_target", size),
+            ping: target("post_processing_ping_target", size),
+            pong: target("post_processing_pong_target", size),
+            bloom: [target("bloom_target_a", bloom_size), target("bloom_target_b", bloom_size)],
+            format
+        }
+    }
+
+    pub fn size(&self) -> [u32; 2] {
+        self.scene.size
+    }
+
+    /// The offscreen target, `None` for the surface.
+    pub fn get(&self, target: PostTarget) -> Option<&WgpuRenderTarget> {
+        match target {
+            PostTarget::Scene => Some(&self.scene),
+            PostTarget::Ping => Some(&self.ping),
+            PostTarget::Pong => Some(&self.pong),
+            PostTarget::BloomA => Some(&self.bloom[0]),
+            PostTarget::BloomB => Some(&self.bloom[1]),
+            PostTarget::Surface => None
+        }
+    }
+}
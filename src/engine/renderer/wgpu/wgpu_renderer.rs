@@ -1,45 +1,133 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
 use std::collections::HashMap;
-use crate::engine::renderer::{Renderable, Renderer, Scene};
+use crate::engine::renderer::{RenderStats, Renderable, Renderer, Scene};
 use std::iter::once;
 use std::mem::offset_of;
 use std::ops::Deref;
 use std::rc::Rc;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use bytemuck::cast_slice;
 use glam::{Mat4, Vec4};
 use wgpu::{BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, Buffer, BufferAddress, BufferBindingType, BufferDescriptor, BufferUsages, ColorTargetState, FragmentState, InstanceDescriptor, Label, RenderPass, RenderPipeline, RenderPipelineDescriptor, ShaderModule, ShaderModuleDescriptor, ShaderSource, ShaderStages, TextureFormat, VertexAttribute, VertexBufferLayout, VertexFormat, VertexState, VertexStepMode};
 use wgpu::core::pipeline::ImplicitLayoutError::BindGroup;
 use wgpu::StoreOp::Store;
 use wgpu::util::{BufferInitDescriptor, DeviceExt};
+use log::{error, info, warn};
 use winit::window::Window;
-use crate::engine::renderer::camera::Camera2D;
-use crate::engine::renderer::material::{ShaderDefinition, Texture};
+use crate::engine::renderer::camera::{Camera2D, ClearPolicy, PixelRect, ScalingMode};
+use crate::engine::renderer::material::{SamplerOptions, ShaderDefinition, Texture, TextureKind};
+use crate::engine::renderer::material_params::MaterialParams;
 use crate::engine::renderer::mesh::{Mesh, Vertex};
-use crate::engine::renderer::shaders::SpriteUniforms;
+use crate::engine::renderer::mipmap::generate_mipmaps_of;
+use crate::engine::renderer::pass_order::{order_passes, PassDependencies};
+use crate::engine::renderer::shader_cache::{ShaderCache, ShaderFileWatcher};
+use crate::engine::renderer::shader_validation::{validate_shader, ShaderError, ShaderErrorKind, ShaderInterface, ShaderReflection};
+use crate::engine::renderer::post_processing::{PostInput, PostPass, PostProcessingStack, PostTarget};
+use crate::engine::renderer::texture_cache::TextureCache;
+use crate::engine::renderer::texture_region::TextureRegion;
+use crate::engine::renderer::shaders::{EffectUniforms, SpriteUniforms, BLIT_SHADER, CLEAR_SHADER, POST_EFFECT_PRELUDE};
+use crate::engine::renderer::wgpu::wgpu_post_processing::WgpuPostProcessingTargets;
+use crate::engine::renderer::wgpu::wgpu_render_target::WgpuRenderTarget;
+use crate::engine::renderer::wgpu::wgpu_sampler::WgpuSamplerCache;
+use crate::engine::renderer::wgpu::wgpu_staging_buffer::{WgpuStagingBuffer, MAX_PENDING_BYTES};
 use crate::engine::renderer::wgpu::wgpu_texture::WgpuTexture;
 
+const SHADER_FILES_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
 pub struct WgpuRenderer<'window> {
     window: Arc<Window>,
-    infra: WgpuInfraPipeline<'window>,
-    texture_cache: HashMap<String, WgpuTexture>,
+    surface: wgpu::Surface<'window>,
+    surface_config: Option<wgpu::SurfaceConfiguration>,
+    infra: Rc<WgpuInfraPipeline>,
+    texture_cache: TextureCache<WgpuTexture>,
     texture_bind_group_layout: wgpu::BindGroupLayout,  
     uniform_bind_group_layout: wgpu::BindGroupLayout, 
+    effect_uniform_bind_group_layout: wgpu::BindGroupLayout,
+    material_bind_group_layouts: HashMap<usize, BindGroupLayout>, // by the number of textures of the material
+    sampler_cache: WgpuSamplerCache,
+    staging_buffer: WgpuStagingBuffer,
+    blit_pipelines: HashMap<TextureFormat, RenderPipeline>,
+    clear_pipelines: HashMap<TextureFormat, RenderPipeline>,
+    pixel_perfect_target: Option<WgpuRenderTarget>,
+    post_processing: PostProcessingStack,
+    post_processing_targets: Option<WgpuPostProcessingTargets>,
+    pipelines: ShaderCache<PipelineVariant, WgpuPipeline>, // of materials and post-processing effects
+    shader_files_poll: u64, // the poll of the shared shader files that the pipelines were last checked against
+    current_frame: Option<WgpuFrame>,
+    frame_index: u64, // counts the frames, for the least recently used textures
+    last_frame_stats: RenderStats
 }
 
-pub struct WgpuInfraPipeline<'window> {
-    surface: wgpu::Surface<'window>,
+/// The surface texture that scenes are drawn on between `begin_frame` and `end_frame`.
+struct WgpuFrame {
+    surface_texture: wgpu::SurfaceTexture,
+    view: wgpu::TextureView,
+    cleared: bool,
+    cleared_targets: Vec<usize>, // render target textures that were already drawn to in this frame
+    passes: Vec<RecordedPass>,
+    stats: RenderStats
+}
+
+/// A scene as seen by one of its cameras, recorded by `render` and drawn in `end_frame`,
+/// once it is known which passes draw the render targets that others sample.
+struct RecordedPass {
+    camera: Camera2D,
+    renderables: Rc<Vec<Renderable>>
+}
+
+/// What a shader is compiled for: a pipeline of the same shader is needed per target format,
+/// and materials with different numbers of textures in their params have different bind group layouts.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+enum PipelineVariant {
+    Material {
+        format: TextureFormat,
+        material_textures: Option<usize> // None for materials without params, which have no group 2
+    },
+    Effect {
+        format: TextureFormat
+    }
+}
+
+/// A compiled shader, with what it declares for the checks at draw time.
+#[derive(Clone)]
+struct WgpuPipeline {
+    pipeline: RenderPipeline,
+    reflection: ShaderReflection
+}
+
+/// A texture that a pass draws to, the surface or a render target.
+struct DrawTarget {
+    view: wgpu::TextureView,
+    size: [u32; 2],
+    format: TextureFormat
+}
+
+/// Where on the frame a camera draws: the scissor rect is the camera's whole region, the viewport
+/// the part of it that the view is mapped onto, and the clear policy what happens to the region first.
+#[derive(Clone, Copy)]
+struct CameraRegion {
+    scissor: PixelRect,
+    viewport: PixelRect,
+    clear: ClearPolicy
+}
+
+/// The GPU resources that are shared by the renderers of all windows.
+pub struct WgpuInfraPipeline {
+    instance: wgpu::Instance,
     adapter: wgpu::Adapter,
     device: wgpu::Device,
     queue: wgpu::Queue,
+    shader_files: RefCell<ShaderFileWatcher> // shared, so that the files are checked once for all windows
 }
 
 impl <'window> WgpuRenderer<'window> {
     pub fn new(window: Arc<Window>) -> WgpuRenderer<'window> {
         let instance_descriptor = InstanceDescriptor::default();
         let instance = wgpu::Instance::new(&instance_descriptor);
-        let size = window.inner_size();
         let window_for_surface = Arc::clone(&window);
-        let surface = unsafe { instance.create_surface(window_for_surface).unwrap() };
+        let surface = instance.create_surface(window_for_surface).unwrap();
         let adapter = pollster::block_on(
             instance.request_adapter(&wgpu::RequestAdapterOptions {
                 power_preference: wgpu::PowerPreference::HighPerformance,
@@ -50,33 +138,78 @@ impl <'window> WgpuRenderer<'window> {
         let (device, queue) = pollster::block_on(
             adapter.request_device(&wgpu::DeviceDescriptor::default(), None)
         ).unwrap();
-        if let Some(config) = surface.get_default_config(&adapter, size.width, size.height) {
-            surface.configure(&device, &config);
-        }
 
-        let texture_bind_group_layout = WgpuRenderer::create_texture_bind_group_layout(&device);
-        let uniform_bind_group_layout = WgpuRenderer::create_uniform_bind_group_layout(&device);
+        let infra = Rc::new(WgpuInfraPipeline {
+            instance,
+            adapter,
+            device,
+            queue,
+            shader_files: RefCell::new(ShaderFileWatcher::new(SHADER_FILES_POLL_INTERVAL))
+        });
+        WgpuRenderer::with_surface(window, surface, infra)
+    }
+
+    /// Creates a renderer for another window that shares the GPU device of an existing renderer.
+    pub fn with_shared_infra(window: Arc<Window>, infra: Rc<WgpuInfraPipeline>) -> WgpuRenderer<'window> {
+        let window_for_surface = Arc::clone(&window);
+        let surface = infra.instance.create_surface(window_for_surface).unwrap();
+        WgpuRenderer::with_surface(window, surface, infra)
+    }
 
-        WgpuRenderer {
-            infra: WgpuInfraPipeline {
-                surface,
-                adapter,
-                device,
-                queue
-            },
-            texture_cache: HashMap::new(),
+    fn with_surface(window: Arc<Window>, surface: wgpu::Surface<'window>, infra: Rc<WgpuInfraPipeline>) -> WgpuRenderer<'window> {
+        let texture_bind_group_layout = WgpuRenderer::create_texture_bind_group_layout(&infra.device);
+        let uniform_bind_group_layout = WgpuRenderer::create_uniform_bind_group_layout(&infra.device);
+        let effect_uniform_bind_group_layout = WgpuRenderer::create_effect_uniform_bind_group_layout(&infra.device);
+
+        let mut renderer = WgpuRenderer {
+            surface,
+            surface_config: None,
+            infra,
+            texture_cache: TextureCache::new(None),
+            frame_index: 0,
             texture_bind_group_layout,
             uniform_bind_group_layout,
+            effect_uniform_bind_group_layout,
+            material_bind_group_layouts: HashMap::new(),
+            sampler_cache: WgpuSamplerCache::default(),
+            staging_buffer: WgpuStagingBuffer::default(),
+            blit_pipelines: HashMap::new(),
+            clear_pipelines: HashMap::new(),
+            pixel_perfect_target: None,
+            post_processing: PostProcessingStack::default(),
+            post_processing_targets: None,
+            pipelines: ShaderCache::new(),
+            shader_files_poll: 0,
+            current_frame: None,
+            last_frame_stats: RenderStats::default(),
             window: Arc::clone(&window)
-        }
+        };
+        let size = window.inner_size();
+        renderer.resize(size.width, size.height);
+        renderer
     }
 
-    fn render_object(&mut self, renderable: &Renderable, camera: &Camera2D, render_pass: &mut RenderPass) {
-        let shader_module = self.create_shader(&renderable.material.shader);
-        let pipeline = self.create_pipeline(
-            format!("{:?}-pipeline", renderable.name).as_str(),
-            shader_module
-        );
+    pub fn infra(&self) -> Rc<WgpuInfraPipeline> {
+        Rc::clone(&self.infra)
+    }
+
+    fn render_object(&mut self, renderable: &Renderable, camera: &Camera2D, format: TextureFormat, render_pass: &mut RenderPass) {
+        let params = &renderable.material.params;
+        let material_textures = (!params.is_empty()).then(|| params.textures().count());
+        let material_bind_group_layout = material_textures
+            .map(|texture_count| self.get_or_create_material_bind_group_layout(texture_count).clone());
+        let variant = PipelineVariant::Material { format, material_textures };
+        let Some(WgpuPipeline { pipeline, reflection }) = self.get_or_create_pipeline(&renderable.material.shader, variant) else {
+            // the shader failed to compile, which was logged when it happened
+            return;
+        };
+        let params_bytes = params.uniform_bytes();
+        let provided_params_size = params_bytes.len() as u64;
+        if let Some(required_params_size) = reflection.uniform_size(2, 0).filter(|size| *size > provided_params_size) {
+            warn!("{} is skipped, its shader {} needs {} bytes of params and the material has {}",
+                renderable.name, renderable.material.shader.name, required_params_size, provided_params_size);
+            return;
+        }
         let vertex_buffer = self.create_vertex_buffer(&renderable.mesh);
         render_pass.set_pipeline(&pipeline);
         render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
@@ -91,22 +224,119 @@ impl <'window> WgpuRenderer<'window> {
         let uniform_bind_group = self.create_uniform_bind_group(uniforms);
         render_pass.set_bind_group(1, &uniform_bind_group, &[]);
 
+        if let Some(layout) = &material_bind_group_layout {
+            let material_bind_group = self.create_material_bind_group(params, &params_bytes, layout);
+            render_pass.set_bind_group(2, &material_bind_group, &[]);
+        }
+
         render_pass.draw(0..renderable.mesh.vertices.len() as u32, 0..1);
     }
 
-    fn create_shader(&self, shader_definition: &ShaderDefinition) -> ShaderModule {
+    fn create_shader(&self, name: &str, source: &str) -> ShaderModule {
         self.infra.device.create_shader_module(ShaderModuleDescriptor {
-            label: Some(shader_definition.name.as_str()),
-            source: ShaderSource::Wgsl(shader_definition.source.clone().into()),
+            label: Some(name),
+            source: ShaderSource::Wgsl(Cow::Owned(source.to_string())),
         })
     }
 
-    fn create_pipeline(&self, pipeline_name: &str, shader_module: ShaderModule) -> RenderPipeline {
-        let preferred_format: TextureFormat = self.infra.surface.get_capabilities(&self.infra.adapter).formats[0];
+    /// The pipeline of a shader, compiled when it is first needed. None if the shader failed to compile,
+    /// which is logged once and retried when the shader's file changes.
+    fn get_or_create_pipeline(&mut self, shader: &Rc<ShaderDefinition>, variant: PipelineVariant) -> Option<WgpuPipeline> {
+        if !self.pipelines.contains(shader, &variant) {
+            let source = self.pipelines.source(shader).to_string();
+            let pipeline = self.compile_pipeline(shader, &source, variant)
+                .inspect_err(|error| error!("{}", error))
+                .ok();
+            self.pipelines.insert(shader, variant, pipeline);
+        }
+        self.pipelines.get(shader, &variant).cloned()
+    }
+
+    /// Compiles a pipeline, with the WGSL and validation errors returned instead of panicking.
+    /// The source is validated with naga first, so that errors point to a line of the shader's file.
+    fn compile_pipeline(&self, shader: &ShaderDefinition, source: &str, variant: PipelineVariant) -> Result<WgpuPipeline, ShaderError> {
+        let interface = match variant {
+            PipelineVariant::Material { material_textures, .. } => ShaderInterface::material(material_textures),
+            PipelineVariant::Effect { .. } => ShaderInterface::effect()
+        };
+        let reflection = validate_shader(&shader.name, shader.path.as_deref(), source, &interface)?;
+
+        let name = shader.name.as_str();
+        self.infra.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let pipeline = match variant {
+            PipelineVariant::Material { format, material_textures } => {
+                let material_bind_group_layout = material_textures
+                    .and_then(|texture_count| self.material_bind_group_layouts.get(&texture_count));
+                self.create_pipeline(
+                    format!("{}-pipeline", name).as_str(),
+                    self.create_shader(name, source),
+                    format,
+                    material_bind_group_layout
+                )
+            },
+            PipelineVariant::Effect { format } => self.create_effect_pipeline(name, source, format)
+        };
+        match pollster::block_on(self.infra.device.pop_error_scope()) {
+            // naga accepted it, so this is a mismatch that the checks of the interface don't cover
+            Some(error) => Err(ShaderError {
+                shader: shader.name.clone(),
+                file: shader.path.clone(),
+                location: None,
+                kind: ShaderErrorKind::Validation,
+                message: error.to_string()
+            }),
+            None => Ok(WgpuPipeline { pipeline, reflection })
+        }
+    }
+
+    /// Recompiles the pipelines of shaders whose files changed, checked once per poll of the shared shader files.
+    /// A shader that fails to compile keeps its previous pipelines until its file is fixed.
+    fn reload_changed_shaders(&mut self) {
+        let poll = self.infra.shader_files.borrow_mut().poll(Instant::now());
+        if poll == self.shader_files_poll {
+            return;
+        }
+        self.shader_files_poll = poll;
 
+        let changes = self.pipelines.poll_changes(&mut self.infra.shader_files.borrow_mut());
+        for change in changes {
+            let compiled: Result<Vec<(PipelineVariant, WgpuPipeline)>, ShaderError> = change.variants.iter()
+                .map(|variant| self.compile_pipeline(&change.shader, &change.source, *variant).map(|pipeline| (*variant, pipeline)))
+                .collect();
+            match compiled {
+                Ok(pipelines) => {
+                    info!("Shader {} reloaded", change.shader.name);
+                    self.pipelines.replace(&change.shader, change.source, pipelines);
+                },
+                Err(error) => error!("{}, keeping the previous version", error)
+            }
+        }
+    }
+
+    /// The format that scenes are drawn in, an sRGB format whenever the surface supports one.
+    pub fn surface_format(&self) -> TextureFormat {
+        match &self.surface_config {
+            Some(config) => config.format,
+            None => choose_surface_format(&self.surface.get_capabilities(&self.infra.adapter).formats)
+                .expect("Surface supports no formats")
+        }
+    }
+
+    /// # Arguments
+    ///
+    /// * `material_bind_group_layout` - The layout of group 2 for materials with parameters
+    fn create_pipeline(
+        &self,
+        pipeline_name: &str,
+        shader_module: ShaderModule,
+        format: TextureFormat,
+        material_bind_group_layout: Option<&BindGroupLayout>
+    ) -> RenderPipeline {
+        let mut bind_group_layouts = vec![&self.texture_bind_group_layout, &self.uniform_bind_group_layout];
+        bind_group_layouts.extend(material_bind_group_layout);
         let pipeline_layout = self.infra.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Render Pipeline Layout"),
-            bind_group_layouts: &[&self.texture_bind_group_layout, &self.uniform_bind_group_layout],
+            bind_group_layouts: &bind_group_layouts,
             push_constant_ranges: &[]
         });
 
@@ -145,7 +375,7 @@ impl <'window> WgpuRenderer<'window> {
                 entry_point: None,
                 compilation_options: Default::default(),
                 targets: &[Some(ColorTargetState {
-                    format: preferred_format,
+                    format,
                     blend: None,
                     write_mask: Default::default(),
                 })]
@@ -158,6 +388,398 @@ impl <'window> WgpuRenderer<'window> {
         })
     }
 
+    fn create_blit_pipeline(&self, format: TextureFormat) -> RenderPipeline {
+        self.create_fullscreen_pipeline(
+            "blit-pipeline",
+            &BLIT_SHADER,
+            &[&self.texture_bind_group_layout],
+            wgpu::BlendState::ALPHA_BLENDING,
+            format
+        )
+    }
+
+    fn create_clear_pipeline(&self, format: TextureFormat) -> RenderPipeline {
+        // replaces the target colour with the blend constant
+        let replace_with_constant = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::Constant,
+            dst_factor: wgpu::BlendFactor::Zero,
+            operation: wgpu::BlendOperation::Add
+        };
+        self.create_fullscreen_pipeline(
+            "clear-pipeline",
+            &CLEAR_SHADER,
+            &[],
+            wgpu::BlendState { color: replace_with_constant, alpha: replace_with_constant },
+            format
+        )
+    }
+
+    fn create_effect_pipeline(&self, name: &str, source: &str, format: TextureFormat) -> RenderPipeline {
+        let shader_definition = ShaderDefinition {
+            name: name.to_string(),
+            source: format!("{}\n{}", POST_EFFECT_PRELUDE, source),
+            path: None
+        };
+        self.create_fullscreen_pipeline(
+            format!("{}-pipeline", name).as_str(),
+            &shader_definition,
+            &[&self.texture_bind_group_layout, &self.effect_uniform_bind_group_layout, &self.texture_bind_group_layout],
+            wgpu::BlendState::REPLACE,
+            format
+        )
+    }
+
+    /// Creates a pipeline for shaders that draw a single triangle covering the viewport, without vertex buffer.
+    fn create_fullscreen_pipeline(
+        &self,
+        pipeline_name: &str,
+        shader_definition: &ShaderDefinition,
+        bind_group_layouts: &[&BindGroupLayout],
+        blend: wgpu::BlendState,
+        format: TextureFormat
+    ) -> RenderPipeline {
+        let shader_module = self.create_shader(&shader_definition.name, &shader_definition.source);
+        let pipeline_layout = self.infra.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some(format!("{}-layout", pipeline_name).as_str()),
+            bind_group_layouts,
+            push_constant_ranges: &[]
+        });
+
+        self.infra.device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some(pipeline_name),
+            layout: Some(&pipeline_layout),
+            vertex: VertexState {
+                module: &shader_module,
+                entry_point: None,
+                compilation_options: Default::default(),
+                buffers: &[],
+            },
+            fragment: Some(FragmentState {
+                module: &shader_module,
+                entry_point: None,
+                compilation_options: Default::default(),
+                targets: &[Some(ColorTargetState {
+                    format,
+                    blend: Some(blend),
+                    write_mask: Default::default(),
+                })]
+            }),
+            primitive: Default::default(),
+            depth_stencil: None,
+            multisample: Default::default(),
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    /// Returns the low resolution target of the pixel perfect scaling mode, recreated when the resolution changed.
+    fn get_or_create_pixel_perfect_target(&mut self, resolution: [u32; 2]) -> &WgpuRenderTarget {
+        let outdated = self.pixel_perfect_target.as_ref().is_none_or(|target| target.size != resolution);
+        if outdated {
+            self.pixel_perfect_target = Some(WgpuRenderTarget::new(
+                &self.infra.device,
+                "pixel_perfect_target",
+                resolution,
+                self.surface_format(),
+                self.sampler_cache.get_or_create(&self.infra.device, SamplerOptions::PIXEL_ART),
+                &self.texture_bind_group_layout
+            ));
+        }
+        self.pixel_perfect_target.as_ref().unwrap()
+    }
+
+    fn draw_renderables(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &DrawTarget,
+        load: wgpu::LoadOp<wgpu::Color>,
+        region: CameraRegion,
+        renderables: &[&Renderable],
+        camera: &Camera2D
+    ) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &target.view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load,
+                    store: Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None
+        });
+        self.prepare_region(&mut render_pass, target.format, region);
+
+        for renderable in renderables {
+            self.render_object(renderable, camera, target.format, &mut render_pass);
+        }
+    }
+
+    /// Draws the texture of a render target stretched over the viewport.
+    fn blit(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &DrawTarget,
+        load: wgpu::LoadOp<wgpu::Color>,
+        region: CameraRegion,
+        source: &wgpu::BindGroup
+    ) {
+        if !self.blit_pipelines.contains_key(&target.format) {
+            self.blit_pipelines.insert(target.format, self.create_blit_pipeline(target.format));
+        }
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Blit Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &target.view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load,
+                    store: Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None
+        });
+        self.prepare_region(&mut render_pass, target.format, region);
+        render_pass.set_pipeline(&self.blit_pipelines[&target.format]);
+        render_pass.set_bind_group(0, source, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    /// Restricts drawing to the camera's region, clears the region if the camera asks for it
+    /// and sets the viewport the camera's view is mapped onto.
+    fn prepare_region(&mut self, render_pass: &mut RenderPass, format: TextureFormat, region: CameraRegion) {
+        let CameraRegion { scissor, viewport, clear } = region;
+        render_pass.set_scissor_rect(scissor.x, scissor.y, scissor.width, scissor.height);
+
+        if let ClearPolicy::Color([red, green, blue, alpha]) = clear {
+            if !self.clear_pipelines.contains_key(&format) {
+                self.clear_pipelines.insert(format, self.create_clear_pipeline(format));
+            }
+            render_pass.set_viewport(scissor.x as f32, scissor.y as f32, scissor.width as f32, scissor.height as f32, 0.0, 1.0);
+            render_pass.set_pipeline(&self.clear_pipelines[&format]);
+            render_pass.set_blend_constant(wgpu::Color {
+                r: red as f64,
+                g: green as f64,
+                b: blue as f64,
+                a: alpha as f64
+            });
+            render_pass.draw(0..3, 0..1);
+        }
+
+        render_pass.set_viewport(viewport.x as f32, viewport.y as f32, viewport.width as f32, viewport.height as f32, 0.0, 1.0);
+    }
+
+    /// Renders the renderables of a scene as seen by one of its cameras, onto the frame or the camera's target.
+    fn render_camera(&mut self, frame: &mut WgpuFrame, renderables: &[Renderable], camera: &Camera2D) {
+        let (target, camera) = match &camera.target {
+            Some(texture) => {
+                let Some(target) = self.get_render_target(texture) else {
+                    return;
+                };
+                let mut camera = camera.clone();
+                camera.update_viewport_size(target.size);
+                (target, Cow::Owned(camera))
+            },
+            None => (self.scene_draw_target(frame), Cow::Borrowed(camera))
+        };
+        let camera = camera.as_ref();
+
+        let (Some(scissor), Some(viewport)) = (
+            clamp_viewport(camera.region(), target.size),
+            clamp_viewport(camera.pixel_viewport(), target.size)
+        ) else {
+            return;
+        };
+        let region = match camera.target {
+            // the view is flipped for render targets, so its region is as well
+            Some(_) => CameraRegion {
+                scissor: flip_vertically(scissor, target.size[1]),
+                viewport: flip_vertically(viewport, target.size[1]),
+                clear: camera.clear
+            },
+            None => CameraRegion { scissor, viewport, clear: camera.clear }
+        };
+
+        // skip everything outside of the camera's view before building draw calls
+        let visible_bounds = camera.visible_bounds();
+        let visible_renderables: Vec<&Renderable> = renderables.iter()
+            .filter(|renderable| renderable.is_visible_in(&visible_bounds))
+            .filter(|renderable| {
+                // a texture can't be sampled while it is drawn to
+                let samples_target = camera.target.as_ref().is_some_and(|target| {
+                    renderable.material.textures().any(|texture| Rc::ptr_eq(texture, target))
+                });
+                if samples_target {
+                    warn!("{} samples the render target it is drawn to and is skipped", renderable.name);
+                }
+                !samples_target
+            })
+            .collect();
+        frame.stats.drawn += visible_renderables.len() as u32;
+        frame.stats.culled += (renderables.len() - visible_renderables.len()) as u32;
+
+        // the first pass to the surface or a render target in a frame clears it, the ones after it draw on top
+        let load = match &camera.target {
+            Some(texture) => {
+                let key = texture_key(texture);
+                let cleared = frame.cleared_targets.contains(&key);
+                frame.cleared_targets.push(key);
+                if cleared { wgpu::LoadOp::Load } else { wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT) }
+            },
+            None => {
+                let cleared = frame.cleared;
+                frame.cleared = true;
+                if cleared { wgpu::LoadOp::Load } else { wgpu::LoadOp::Clear(wgpu::Color::BLACK) }
+            }
+        };
+
+        let mut encoder = self.create_frame_encoder("Render Encoder");
+
+        match camera.scaling_mode {
+            ScalingMode::PixelPerfect { resolution } => {
+                // draw at the low resolution first, then upscale it with nearest filtering
+                let pixel_perfect_target = self.get_or_create_pixel_perfect_target(resolution);
+                let pixel_perfect_bind_group = pixel_perfect_target.bind_group.clone();
+                let pixel_perfect_draw_target = DrawTarget {
+                    view: pixel_perfect_target.view.clone(),
+                    size: pixel_perfect_target.size,
+                    format: pixel_perfect_target.texture.format()
+                };
+                let pixel_perfect_rect = PixelRect { x: 0, y: 0, width: pixel_perfect_target.size[0], height: pixel_perfect_target.size[1] };
+                let pixel_perfect_region = CameraRegion { scissor: pixel_perfect_rect, viewport: pixel_perfect_rect, clear: ClearPolicy::Keep };
+                self.draw_renderables(&mut encoder, &pixel_perfect_draw_target, wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT), pixel_perfect_region, &visible_renderables, camera);
+                self.blit(&mut encoder, &target, load, region, &pixel_perfect_bind_group);
+            },
+            _ => self.draw_renderables(&mut encoder, &target, load, region, &visible_renderables, camera)
+        }
+
+        self.infra.queue.submit(once(encoder.finish()));
+    }
+
+    /// What the cameras without a render target draw to: the surface, or the offscreen target
+    /// that the post-processing effects read from when there are any.
+    fn scene_draw_target(&mut self, frame: &WgpuFrame) -> DrawTarget {
+        let size = [frame.surface_texture.texture.width(), frame.surface_texture.texture.height()];
+        let format = frame.surface_texture.texture.format();
+        if self.post_processing.is_empty() {
+            return DrawTarget { view: frame.view.clone(), size, format };
+        }
+
+        let outdated = self.post_processing_targets.as_ref()
+            .is_none_or(|targets| targets.size() != size || targets.format != format);
+        if outdated {
+            self.post_processing_targets = Some(WgpuPostProcessingTargets::new(
+                &self.infra.device,
+                size,
+                format,
+                self.sampler_cache.get_or_create(&self.infra.device, SamplerOptions::TRILINEAR),
+                &self.texture_bind_group_layout
+            ));
+        }
+        let scene = &self.post_processing_targets.as_ref().unwrap().scene;
+        DrawTarget { view: scene.view.clone(), size, format }
+    }
+
+    /// Runs the post-processing passes on the drawn frame, the last one draws to the surface.
+    fn apply_post_processing(&mut self, frame: &WgpuFrame) {
+        let passes = self.post_processing.passes();
+        // textures of the effects are uploaded before the targets are borrowed
+        let textures: Vec<Option<wgpu::BindGroup>> = passes.iter()
+            .map(|pass| match &pass.extra {
+                PostInput::Texture(texture) => Some(self.get_or_create_texture(texture).bind_group.clone()),
+                PostInput::Target(_) => None
+            })
+            .collect();
+        let format = frame.surface_texture.texture.format();
+        let pipelines: Vec<Option<RenderPipeline>> = passes.iter()
+            .map(|pass| self.get_or_create_pipeline(&pass.shader, PipelineVariant::Effect { format }).map(|compiled| compiled.pipeline))
+            .collect();
+        // effects that failed to compile pass the frame on as it is
+        if !self.blit_pipelines.contains_key(&format) {
+            self.blit_pipelines.insert(format, self.create_blit_pipeline(format));
+        }
+
+        if self.post_processing_targets.is_none() {
+            return;
+        }
+        let mut encoder = self.create_frame_encoder("Post Processing Encoder");
+        let targets = self.post_processing_targets.as_ref().unwrap();
+        for ((pass, texture), pipeline) in passes.iter().zip(&textures).zip(&pipelines) {
+            let source = targets.get(pass.source).expect("Post-processing passes don't read from the surface");
+            let extra = match &pass.extra {
+                PostInput::Target(target) => &targets.get(*target).expect("Post-processing passes don't read from the surface").bind_group,
+                PostInput::Texture(_) => texture.as_ref().unwrap()
+            };
+            let (view, size) = match targets.get(pass.output) {
+                Some(output) => (&output.view, output.size),
+                None => (&frame.view, targets.size())
+            };
+            let uniforms = EffectUniforms {
+                params: pass.params,
+                resolution: [size[0] as f32, size[1] as f32],
+                texel_size: [1.0 / source.size[0] as f32, 1.0 / source.size[1] as f32]
+            };
+            let uniform_bind_group = self.create_effect_uniform_bind_group(uniforms);
+
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some(pass.shader.name.as_str()),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None
+            });
+            render_pass.set_bind_group(0, &source.bind_group, &[]);
+            match pipeline {
+                Some(pipeline) => {
+                    render_pass.set_pipeline(pipeline);
+                    render_pass.set_bind_group(1, &uniform_bind_group, &[]);
+                    render_pass.set_bind_group(2, extra, &[]);
+                },
+                None => render_pass.set_pipeline(&self.blit_pipelines[&format])
+            }
+            render_pass.draw(0..3, 0..1);
+        }
+        self.infra.queue.submit(once(encoder.finish()));
+    }
+
+    /// Replaces the post-processing effects, applied from the next frame on. No effects draws the layers straight to the surface.
+    pub fn set_post_processing(&mut self, stack: PostProcessingStack) {
+        if stack.is_empty() {
+            self.post_processing_targets = None;
+        }
+        self.post_processing = stack;
+    }
+
+    /// The GPU texture of a camera's render target, created when it is first drawn to.
+    fn get_render_target(&mut self, texture: &Rc<Texture>) -> Option<DrawTarget> {
+        if texture.kind != TextureKind::RenderTarget {
+            warn!("Texture {} is not a render target and can't be drawn to", texture.name);
+            return None;
+        }
+        let wgpu_texture = self.get_or_create_texture(texture);
+        let target = DrawTarget {
+            view: wgpu_texture.view.clone(),
+            size: [texture.width, texture.height],
+            format: wgpu_texture.texture.format()
+        };
+        // the contents only exist on the GPU, so it must not be evicted to stay within the budget
+        self.texture_cache.mark_dynamic(texture);
+        Some(target)
+    }
+
     fn create_vertex_buffer(&self, mesh: &Mesh) -> Buffer {
         self.infra.device.create_buffer_init(&BufferInitDescriptor {
             label: Some(format!("{:?}-vertex-buffer", mesh.name).as_str()),
@@ -190,17 +812,97 @@ impl <'window> WgpuRenderer<'window> {
         })
     }
 
-    fn get_or_create_texture(&mut self, abstract_texture: &Texture) -> &WgpuTexture {
-        if !self.texture_cache.contains_key(&abstract_texture.name) {
-            let wgpu_texture = WgpuTexture::from_abstract_texture(
-                &self.infra.device,
-                &self.infra.queue,
+    fn get_or_create_texture(&mut self, abstract_texture: &Rc<Texture>) -> &WgpuTexture {
+        let infra = &self.infra;
+        let sampler_cache = &mut self.sampler_cache;
+        let texture_bind_group_layout = &self.texture_bind_group_layout;
+        self.texture_cache.get_or_insert_with(abstract_texture, self.frame_index, || {
+            WgpuTexture::from_abstract_texture(
+                &infra.device,
+                &infra.queue,
                 abstract_texture,
-                &self.texture_bind_group_layout,
-            );
-            self.texture_cache.insert(abstract_texture.name.clone(), wgpu_texture);
+                sampler_cache.get_or_create(&infra.device, abstract_texture.sampler),
+                texture_bind_group_layout,
+            )
+        })
+    }
+
+    /// Replaces the pixels of a texture on the GPU, e.g. for video frames or a procedurally generated minimap.
+    /// The texture's own data is left as it is, so the texture is no longer evicted to stay within the memory budget.
+    ///
+    /// # Arguments
+    ///
+    /// * `texture` - The texture to update, uploaded first if it wasn't drawn yet
+    /// * `pixels` - RGBA pixels of the whole texture, bottom row first like `Texture::data`
+    pub fn update_texture(&mut self, texture: &Rc<Texture>, pixels: &[u8]) -> Result<(), String> {
+        self.update_texture_region(texture, TextureRegion::full(texture), pixels)
+    }
+
+    /// Replaces the pixels of a part of a texture on the GPU, e.g. for a paint tool or a fog of war mask.
+    /// Textures with mipmaps can only be updated as a whole, so that their smaller levels are regenerated.
+    ///
+    /// # Arguments
+    ///
+    /// * `texture` - The texture to update, uploaded first if it wasn't drawn yet
+    /// * `region` - The part to replace, with the origin at the top left
+    /// * `pixels` - RGBA pixels of the region, bottom row first like `Texture::data`
+    pub fn update_texture_region(&mut self, texture: &Rc<Texture>, region: TextureRegion, pixels: &[u8]) -> Result<(), String> {
+        if region.x.saturating_add(region.width) > texture.width || region.y.saturating_add(region.height) > texture.height {
+            return Err(format!("Region {:?} lies outside of texture {} of {}x{}", region, texture.name, texture.width, texture.height));
+        }
+        let expected_bytes = region.width as usize * region.height as usize * 4;
+        if pixels.len() != expected_bytes {
+            return Err(format!("Update of texture {} has {} bytes instead of {}", texture.name, pixels.len(), expected_bytes));
+        }
+        let whole_texture = region.width == texture.width && region.height == texture.height;
+        if texture.mipmaps && !whole_texture {
+            return Err(format!("Texture {} has mipmaps and can only be updated as a whole", texture.name));
+        }
+
+        self.get_or_create_texture(texture);
+        self.texture_cache.mark_dynamic(texture);
+        let wgpu_texture = self.texture_cache.get(texture).unwrap();
+        // textures are stored bottom row first
+        let origin = [region.x, texture.height - region.y - region.height];
+        self.staging_buffer.queue_upload(&wgpu_texture.texture, origin, [region.width, region.height], pixels);
+        if texture.mipmaps {
+            for (index, level) in generate_mipmaps_of(texture.width, texture.height, pixels, texture.color_space).iter().enumerate() {
+                WgpuTexture::write_level(&self.infra.queue, &wgpu_texture.texture, index as u32 + 1, level.width, level.height, &level.data);
+            }
+        }
+        if self.staging_buffer.pending_bytes() > MAX_PENDING_BYTES {
+            let encoder = self.create_frame_encoder("Texture Upload Encoder");
+            self.infra.queue.submit(once(encoder.finish()));
         }
-        self.texture_cache.get(&abstract_texture.name).unwrap()
+        Ok(())
+    }
+
+    /// An encoder that starts with the copies of the pending texture updates,
+    /// so that they are applied before anything the encoder draws.
+    fn create_frame_encoder(&mut self, label: &str) -> wgpu::CommandEncoder {
+        let mut encoder = self.infra.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some(label),
+        });
+        self.staging_buffer.record_copies(&self.infra.device, &self.infra.queue, &mut encoder);
+        encoder
+    }
+
+    /// Frees the GPU memory of a texture right away, it is uploaded again if it is drawn later on.
+    pub fn unload_texture(&mut self, texture: &Rc<Texture>) {
+        if self.texture_cache.unload(texture) {
+            info!("Texture {} unloaded", texture.name);
+        }
+    }
+
+    /// Limits the GPU memory of the textures, in bytes. Textures that weren't drawn for the longest time
+    /// are evicted after a frame when the limit is exceeded. Unlimited when None.
+    pub fn set_texture_memory_budget(&mut self, memory_budget: Option<u64>) {
+        self.texture_cache.set_memory_budget(memory_budget);
+    }
+
+    /// The estimated GPU memory of the cached textures, in bytes.
+    pub fn texture_memory_used(&self) -> u64 {
+        self.texture_cache.memory_used()
     }
 
     fn create_uniform_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
@@ -221,6 +923,121 @@ impl <'window> WgpuRenderer<'window> {
         })
     }
 
+    fn create_effect_uniform_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("effect_uniform_bind_group_layout"),
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None
+                    },
+                    count: None
+                }
+            ],
+        })
+    }
+
+    fn create_effect_uniform_bind_group(&self, uniforms: EffectUniforms) -> wgpu::BindGroup {
+        let uniform_buffer = self.infra.device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("effect_uniform_buffer"),
+            contents: bytemuck::cast_slice(&[uniforms]),
+            usage: BufferUsages::UNIFORM,
+        });
+
+        self.infra.device.create_bind_group(&BindGroupDescriptor {
+            label: Some("effect_uniform_bind_group"),
+            layout: &self.effect_uniform_bind_group_layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding()
+                }
+            ]
+        })
+    }
+
+    /// The layout of group 2 for materials with parameters: their uniforms at binding 0,
+    /// followed by a texture and a sampler binding per texture.
+    fn get_or_create_material_bind_group_layout(&mut self, texture_count: usize) -> &BindGroupLayout {
+        let device = &self.infra.device;
+        self.material_bind_group_layouts.entry(texture_count).or_insert_with(|| {
+            let mut entries = vec![BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None
+                },
+                count: None
+            }];
+            for index in 0..texture_count as u32 {
+                entries.push(BindGroupLayoutEntry {
+                    binding: 1 + index * 2,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None
+                });
+                entries.push(BindGroupLayoutEntry {
+                    binding: 2 + index * 2,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None
+                });
+            }
+            device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+                label: Some(format!("material_bind_group_layout_{}_textures", texture_count).as_str()),
+                entries: &entries
+            })
+        })
+    }
+
+    /// # Arguments
+    ///
+    /// * `uniform_bytes` - The `uniform_bytes` of the params, computed once per draw
+    fn create_material_bind_group(&mut self, params: &MaterialParams, uniform_bytes: &[u8], layout: &BindGroupLayout) -> wgpu::BindGroup {
+        let uniform_buffer = self.infra.device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("material_uniform_buffer"),
+            contents: uniform_bytes,
+            usage: BufferUsages::UNIFORM,
+        });
+        let textures: Vec<(wgpu::TextureView, wgpu::Sampler)> = params.textures()
+            .map(|texture| {
+                let view = self.get_or_create_texture(texture).view.clone();
+                let sampler = self.sampler_cache.get_or_create(&self.infra.device, texture.sampler).clone();
+                (view, sampler)
+            })
+            .collect();
+
+        let mut entries = vec![BindGroupEntry {
+            binding: 0,
+            resource: uniform_buffer.as_entire_binding()
+        }];
+        for (index, (view, sampler)) in textures.iter().enumerate() {
+            entries.push(BindGroupEntry {
+                binding: 1 + index as u32 * 2,
+                resource: BindingResource::TextureView(view)
+            });
+            entries.push(BindGroupEntry {
+                binding: 2 + index as u32 * 2,
+                resource: BindingResource::Sampler(sampler)
+            });
+        }
+        self.infra.device.create_bind_group(&BindGroupDescriptor {
+            label: Some("material_bind_group"),
+            layout,
+            entries: &entries
+        })
+    }
+
     fn create_uniform_bind_group(&self, uniforms: SpriteUniforms) -> wgpu::BindGroup {
         let uniform_buffer = self.infra.device.create_buffer_init(&BufferInitDescriptor {
             label: Some("uniform_buffer"),
@@ -242,51 +1059,164 @@ impl <'window> WgpuRenderer<'window> {
 }
 
 impl<'window> Renderer for WgpuRenderer<'window> {
-    fn render(&mut self, scene: &dyn Scene) {
-        let frame = self.infra.surface
+    fn begin_frame(&mut self) {
+        if cfg!(debug_assertions) {
+            self.reload_changed_shaders();
+        }
+        let surface_texture = self.surface
             .get_current_texture()
             .expect("Failed to acquire next swap chain texture");
-        let view = frame
+        let view = surface_texture
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
-        let render_pass_desc = wgpu::RenderPassDescriptor {
-            label: Some("Render Pass"),
-            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: &view,
-                resolve_target: None,
-                ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(wgpu::Color {
-                        r: 0.0,
-                        g: 0.0,
-                        b: 0.0,
-                        a: 1.0,
-                    }),
-                    store: Store,
-                },
-            })],
-            depth_stencil_attachment: None,
-            timestamp_writes: None,
-            occlusion_query_set: None
+        self.current_frame = Some(WgpuFrame {
+            surface_texture,
+            view,
+            cleared: false,
+            cleared_targets: Vec::new(),
+            passes: Vec::new(),
+            stats: RenderStats::default()
+        });
+    }
+
+    fn render(&mut self, scene: &dyn Scene) {
+        let Some(frame) = self.current_frame.as_mut() else {
+            warn!("Scene rendered outside of a frame");
+            return;
         };
 
-        let mut encoder =
-            self.infra.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("Render Encoder"),
+        let renderables = Rc::new(scene.get_renderables());
+        for camera in scene.get_cameras() {
+            frame.passes.push(RecordedPass {
+                camera: camera.clone(),
+                renderables: Rc::clone(&renderables)
             });
+        }
+    }
 
-        {
-            let mut render_pass = encoder.begin_render_pass(&render_pass_desc);
-            
-            for renderable in scene.get_renderables() {
-                // TODO: this might be problematic, as we draw multiple times before submitting the command buffer.
-                // so it might only draw the last renderable we added to the command buffer.
-                // see https://webgpufundamentals.org/webgpu/lessons/webgpu-uniforms.html
-                self.render_object(&renderable, scene.get_camera().deref(), &mut render_pass);    
+    fn end_frame(&mut self) {
+        if let Some(mut frame) = self.current_frame.take() {
+            // render targets are drawn before the passes that sample them
+            let passes = std::mem::take(&mut frame.passes);
+            let dependencies: Vec<PassDependencies<usize>> = passes.iter()
+                .map(|pass| PassDependencies {
+                    target: pass.camera.target.as_ref().map(texture_key),
+                    sampled: pass.renderables.iter()
+                        .flat_map(|renderable| renderable.material.textures().map(texture_key))
+                        .collect()
+                })
+                .collect();
+            for index in order_passes(&dependencies) {
+                self.render_camera(&mut frame, &passes[index].renderables, &passes[index].camera);
+            }
+
+            if !frame.cleared {
+                // nothing was rendered, but the surface still has to be cleared before presenting it
+                let target = self.scene_draw_target(&frame);
+                let mut encoder = self.create_frame_encoder("Clear Encoder");
+                encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Clear Pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &target.view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                            store: Store,
+                        },
+                    })],
+                    depth_stencil_attachment: None,
+                    timestamp_writes: None,
+                    occlusion_query_set: None
+                });
+                self.infra.queue.submit(once(encoder.finish()));
             }
+            if !self.post_processing.is_empty() {
+                self.apply_post_processing(&frame);
+            }
+            frame.surface_texture.present();
+
+            self.pipelines.collect();
+            let evictions = self.texture_cache.collect(self.frame_index);
+            for eviction in &evictions {
+                info!("Texture {} evicted ({:?}), {} bytes freed", eviction.name, eviction.reason, eviction.bytes);
+            }
+            self.last_frame_stats = RenderStats {
+                evicted_textures: evictions.len() as u32,
+                ..frame.stats
+            };
+            self.frame_index += 1;
         }
+    }
 
-        self.infra.queue.submit(once(encoder.finish()));
-        frame.present();
+    fn resize(&mut self, width: u32, height: u32) {
+        // a minimised window has a zero sized surface, which can't be configured
+        if width == 0 || height == 0 {
+            return;
+        }
+        if let Some(mut config) = self.surface.get_default_config(&self.infra.adapter, width, height) {
+            let formats = self.surface.get_capabilities(&self.infra.adapter).formats;
+            config.format = choose_surface_format(&formats).unwrap_or(config.format);
+            if self.surface_config.as_ref().is_none_or(|previous| previous.format != config.format) {
+                info!("Surface format of window {:?}: {:?}", self.window.id(), config.format);
+                if !config.format.is_srgb() {
+                    warn!("Surface has no sRGB format, colours will look darker than intended");
+                }
+            }
+            self.surface.configure(&self.infra.device, &config);
+            self.surface_config = Some(config);
+        }
     }
 
-}
\ No newline at end of file
+    fn stats(&self) -> RenderStats {
+        self.last_frame_stats
+    }
+
+}
+
+/// Mirrors a rectangle with its origin at the top left to one with its origin at the bottom left.
+fn flip_vertically(rect: PixelRect, target_height: u32) -> PixelRect {
+    PixelRect {
+        y: target_height - rect.y - rect.height,
+        ..rect
+    }
+}
+
+/// Identifies a shared texture by its address, which stays unique while the texture is alive.
+fn texture_key(texture: &Rc<Texture>) -> usize {
+    Rc::as_ptr(texture) as usize
+}
+
+/// Prefers an sRGB format, so that the linear colours of the shaders are encoded by the GPU in the same way
+/// on every platform, instead of whichever format the platform lists first.
+fn choose_surface_format(formats: &[TextureFormat]) -> Option<TextureFormat> {
+    formats.iter().copied().find(TextureFormat::is_srgb).or(formats.first().copied())
+}
+
+/// Restricts a viewport to the bounds of its target, wgpu rejects viewports that extend beyond it.
+fn clamp_viewport(viewport: PixelRect, target_size: [u32; 2]) -> Option<PixelRect> {
+    let x = viewport.x.min(target_size[0]);
+    let y = viewport.y.min(target_size[1]);
+    let width = viewport.width.min(target_size[0] - x);
+    let height = viewport.height.min(target_size[1] - y);
+    if width == 0 || height == 0 {
+        return None;
+    }
+    Some(PixelRect { x, y, width, height })
+}
+
+#[cfg(test)]
+mod tests {
+    use wgpu::TextureFormat;
+    use crate::engine::renderer::wgpu::wgpu_renderer::choose_surface_format;
+
+    #[test]
+    fn choose_surface_format_should_prefer_srgb() {
+        // given: a platform that lists a linear format first
+        let formats = [TextureFormat::Bgra8Unorm, TextureFormat::Bgra8UnormSrgb];
+
+        // then
+        assert_eq!(choose_surface_format(&formats), Some(TextureFormat::Bgra8UnormSrgb));
+        assert_eq!(choose_surface_format(&[TextureFormat::Rgba16Float]), Some(TextureFormat::Rgba16Float));
+        assert_eq!(choose_surface_format(&[]), None);
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+use log::warn;
+use crate::engine::renderer::material::{AddressMode, FilterMode, SamplerOptions};
+
+/// Creates every distinct sampler only once, textures with the same options share it.
+#[derive(Default)]
+pub struct WgpuSamplerCache {
+    samplers: HashMap<SamplerOptions, wgpu::Sampler>
+}
+
+impl WgpuSamplerCache {
+
+    pub fn get_or_create(&mut self, device: &wgpu::Device, options: SamplerOptions) -> &wgpu::Sampler {
+        self.samplers.entry(options).or_insert_with(|| {
+            device.create_sampler(&sampler_descriptor(options))
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.samplers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samplers.is_empty()
+    }
+}
+
+fn sampler_descriptor(options: SamplerOptions) -> wgpu::SamplerDescriptor<'static> {
+    // wgpu rejects anisotropic filtering unless every filter is linear
+    let all_linear = options.mag_filter == FilterMode::Linear
+        && options.min_filter == FilterMode::Linear
+        && options.mipmap_filter == FilterMode::Linear;
+    let anisotropy = options.anisotropy.clamp(1, 16);
+    let anisotropy_clamp = if anisotropy > 1 && !all_linear {
+        warn!("Anisotropy {} requires linear filters, falling back to 1 for {:?}", anisotropy, options);
+        1
+    } else {
+        anisotropy
+    };
+
+    wgpu::SamplerDescriptor {
+        label: Some("texture_sampler"),
+        address_mode_u: address_mode(options.address_mode_u),
+        address_mode_v: address_mode(options.address_mode_v),
+        address_mode_w: wgpu::AddressMode::ClampToEdge,
+        mag_filter: filter_mode(options.mag_filter),
+        min_filter: filter_mode(options.min_filter),
+        mipmap_filter: filter_mode(options.mipmap_filter),
+        anisotropy_clamp,
+        ..Default::default()
+    }
+}
+
+fn filter_mode(filter_mode: FilterMode) -> wgpu::FilterMode {
+    match filter_mode {
+        FilterMode::Nearest => wgpu::FilterMode::Nearest,
+        FilterMode::Linear => wgpu::FilterMode::Linear
+    }
+}
+
+fn address_mode(address_mode: AddressMode) -> wgpu::AddressMode {
+    match address_mode {
+        AddressMode::ClampToEdge => wgpu::AddressMode::ClampToEdge,
+        AddressMode::Repeat => wgpu::AddressMode::Repeat,
+        AddressMode::MirrorRepeat => wgpu::AddressMode::MirrorRepeat
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::renderer::material::{AddressMode, FilterMode, SamplerOptions};
+    use crate::engine::renderer::wgpu::wgpu_sampler::sampler_descriptor;
+
+    #[test]
+    fn sampler_descriptor_should_map_filters_and_address_modes() {
+        // given
+        let options = SamplerOptions {
+            address_mode_u: AddressMode::Repeat,
+            address_mode_v: AddressMode::MirrorRepeat,
+            ..SamplerOptions::PIXEL_ART
+        };
+
+        // when
+        let descriptor = sampler_descriptor(options);
+
+        // then
+        assert_eq!(descriptor.mag_filter, wgpu::FilterMode::Nearest);
+        assert_eq!(descriptor.address_mode_u, wgpu::AddressMode::Repeat);
+        assert_eq!(descriptor.address_mode_v, wgpu::AddressMode::MirrorRepeat);
+    }
+
+    #[test]
+    fn sampler_descriptor_should_only_allow_anisotropy_with_linear_filters() {
+        // given
+        let linear = SamplerOptions {
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            mipmap_filter: FilterMode::Linear,
+            anisotropy: 32,
+            ..SamplerOptions::default()
+        };
+        let nearest = SamplerOptions {
+            anisotropy: 8,
+            ..SamplerOptions::PIXEL_ART
+        };
+
+        // then
+        assert_eq!(sampler_descriptor(linear).anisotropy_clamp, 16);
+        assert_eq!(sampler_descriptor(nearest).anisotropy_clamp, 1);
+    }
+}
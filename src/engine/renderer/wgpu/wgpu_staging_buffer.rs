@@ -0,0 +1,177 @@
+const BYTES_PER_PIXEL: u32 = 4;
+
+/// Pending updates larger than this are copied right away instead of with the next frame,
+/// e.g. while a hidden window keeps streaming a texture without rendering.
+pub const MAX_PENDING_BYTES: usize = 16 * 1024 * 1024;
+
+/// A buffer that texture updates are copied through, kept between frames and only replaced when the
+/// updates of a frame need more room. The updates are collected in a scratch buffer that is reused as well,
+/// with their rows padded the way buffer to texture copies require, and copied by the frame's encoder.
+#[derive(Default)]
+pub struct WgpuStagingBuffer {
+    buffer: Option<wgpu::Buffer>,
+    pending: PendingUploads<wgpu::Texture>
+}
+
+impl WgpuStagingBuffer {
+
+    /// Collects RGBA pixels for a rectangle of mip level 0 of a texture, copied by `record_copies`.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - The corner of the rectangle with the lowest coordinates in the stored texture
+    /// * `size` - Width and height of the rectangle in pixels
+    /// * `pixels` - Tightly packed rows of the rectangle, in the order they are stored in
+    pub fn queue_upload(&mut self, texture: &wgpu::Texture, origin: [u32; 2], size: [u32; 2], pixels: &[u8]) {
+        self.pending.push(texture.clone(), origin, size, pixels);
+    }
+
+    pub fn pending_bytes(&self) -> usize {
+        self.pending.bytes.len()
+    }
+
+    /// Writes the pending updates to the buffer and records their copies into an encoder,
+    /// which has to be submitted before the next updates are recorded.
+    pub fn record_copies(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, encoder: &mut wgpu::CommandEncoder) {
+        if self.pending.copies.is_empty() {
+            return;
+        }
+        let current_size = self.buffer.as_ref().map(|buffer| buffer.size());
+        if let Some(size) = grown_buffer_size(current_size, self.pending.bytes.len() as u64) {
+            self.buffer = Some(device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("texture_staging_buffer"),
+                size,
+                usage: wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false
+            }));
+        }
+        let buffer = self.buffer.as_ref().unwrap();
+        queue.write_buffer(buffer, 0, &self.pending.bytes);
+
+        for copy in &self.pending.copies {
+            let [width, height] = copy.size;
+            encoder.copy_buffer_to_texture(
+                wgpu::TexelCopyBufferInfo {
+                    buffer,
+                    layout: wgpu::TexelCopyBufferLayout {
+                        offset: copy.offset,
+                        bytes_per_row: Some(padded_bytes_per_row(width)),
+                        rows_per_image: Some(height),
+                    },
+                },
+                wgpu::TexelCopyTextureInfo {
+                    texture: &copy.texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d { x: copy.origin[0], y: copy.origin[1], z: 0 },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+        self.pending.clear();
+    }
+}
+
+/// Updates waiting to be copied, their padded rows one after the other.
+struct PendingUploads<T> {
+    bytes: Vec<u8>, // keeps its capacity between frames
+    copies: Vec<PendingCopy<T>>
+}
+
+struct PendingCopy<T> {
+    texture: T,
+    offset: u64, // into the bytes, in bytes
+    origin: [u32; 2],
+    size: [u32; 2]
+}
+
+impl<T> Default for PendingUploads<T> {
+    fn default() -> Self {
+        Self { bytes: Vec::new(), copies: Vec::new() }
+    }
+}
+
+impl<T> PendingUploads<T> {
+
+    fn push(&mut self, texture: T, origin: [u32; 2], size: [u32; 2], pixels: &[u8]) {
+        let [width, height] = size;
+        if width == 0 || height == 0 {
+            return;
+        }
+        // the padded rows keep every copy's offset aligned as well
+        let offset = self.bytes.len() as u64;
+        let bytes_per_row = (width * BYTES_PER_PIXEL) as usize;
+        let padding = padded_bytes_per_row(width) as usize - bytes_per_row;
+        for row in pixels.chunks_exact(bytes_per_row) {
+            self.bytes.extend_from_slice(row);
+            self.bytes.resize(self.bytes.len() + padding, 0);
+        }
+        self.copies.push(PendingCopy { texture, offset, origin, size });
+    }
+
+    fn clear(&mut self) {
+        self.bytes.clear();
+        self.copies.clear();
+    }
+}
+
+/// Copies from buffers to textures need rows that start at multiples of 256 bytes.
+fn padded_bytes_per_row(width: u32) -> u32 {
+    let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    (width * BYTES_PER_PIXEL).div_ceil(alignment) * alignment
+}
+
+/// The size of a new buffer when the current one is too small for the pending bytes, None when it can be reused.
+fn grown_buffer_size(current_size: Option<u64>, required_size: u64) -> Option<u64> {
+    match current_size {
+        Some(size) if size >= required_size => None,
+        _ => Some(required_size.next_power_of_two())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::renderer::wgpu::wgpu_staging_buffer::{grown_buffer_size, padded_bytes_per_row, PendingUploads};
+
+    #[test]
+    fn pending_uploads_should_align_rows_and_copies_to_256_bytes() {
+        // given: two rows of 3 pixels, then a single pixel
+        let mut pending = PendingUploads::default();
+        let pixels: Vec<u8> = (0..24).collect();
+
+        // when
+        pending.push("first", [0, 0], [3, 2], &pixels);
+        pending.push("second", [5, 5], [1, 1], &[1, 2, 3, 4]);
+
+        // then
+        assert_eq!(padded_bytes_per_row(3), 256);
+        assert_eq!(pending.bytes.len(), 3 * 256);
+        assert_eq!(pending.bytes[0..12], pixels[0..12]);
+        assert_eq!(pending.bytes[256..268], pixels[12..24]);
+        let offsets: Vec<u64> = pending.copies.iter().map(|copy| copy.offset).collect();
+        assert_eq!(offsets, vec![0, 512]);
+        assert_eq!(pending.bytes[512..516], [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn staging_buffer_should_be_reused_by_updates_that_fit() {
+        // given: the updates of a frame were copied
+        let mut pending = PendingUploads::default();
+        pending.push("texture", [0, 0], [64, 64], &[0; 64 * 64 * 4]);
+        let buffer_size = grown_buffer_size(None, pending.bytes.len() as u64).unwrap();
+        let scratch = pending.bytes.as_ptr();
+        pending.clear();
+
+        // when: a smaller update follows
+        pending.push("texture", [0, 0], [16, 16], &[0; 16 * 16 * 4]);
+
+        // then: neither the scratch bytes nor the buffer are replaced, only a larger update grows the buffer
+        assert_eq!(pending.bytes.as_ptr(), scratch);
+        assert_eq!(grown_buffer_size(Some(buffer_size), pending.bytes.len() as u64), None);
+        assert_eq!(grown_buffer_size(Some(buffer_size), buffer_size + 1), Some(buffer_size * 2));
+    }
+}
@@ -1,9 +1,9 @@
-use crate::engine::renderer::material::Texture;
+use crate::engine::renderer::material::{ColorSpace, Texture, TextureKind};
+use crate::engine::renderer::mipmap::{generate_mipmaps, mip_level_count};
 
 pub struct WgpuTexture {
     pub texture: wgpu::Texture,
     pub view: wgpu::TextureView,
-    pub sampler: wgpu::Sampler,
     pub bind_group: wgpu::BindGroup
 }
 
@@ -15,8 +15,19 @@ impl WgpuTexture {
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         abstract_texture: &Texture,
+        sampler: &wgpu::Sampler,
         bind_group_layout: &wgpu::BindGroupLayout,
     ) -> Self {
+        let render_target = abstract_texture.kind == TextureKind::RenderTarget;
+        let mip_level_count = if abstract_texture.mipmaps && !render_target {
+            mip_level_count(abstract_texture.width, abstract_texture.height)
+        } else {
+            1
+        };
+        let format = match abstract_texture.color_space {
+            ColorSpace::Srgb => wgpu::TextureFormat::Rgba8UnormSrgb,
+            ColorSpace::Linear => wgpu::TextureFormat::Rgba8Unorm
+        };
         let texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some(&abstract_texture.name),
             size: wgpu::Extent3d {
@@ -24,44 +35,29 @@ impl WgpuTexture {
                 height: abstract_texture.height,
                 depth_or_array_layers: 1,
             },
-            mip_level_count: 1,
+            mip_level_count,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8UnormSrgb,
-            view_formats: &[wgpu::TextureFormat::Rgba8UnormSrgb],
-            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-        });
-        
-        queue.write_texture(
-            wgpu::TexelCopyTextureInfo {
-                texture: &texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-                aspect: wgpu::TextureAspect::All,
+            format,
+            view_formats: &[format],
+            usage: if render_target {
+                wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::RENDER_ATTACHMENT
+            } else {
+                wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST
             },
-            &abstract_texture.data,
-            wgpu::TexelCopyBufferLayout {
-                offset: 0,
-                bytes_per_row: Some(Self::RGBA_BYTES * abstract_texture.width),
-                rows_per_image: Some(abstract_texture.height),
-            },
-            wgpu::Extent3d {
-                width: abstract_texture.width,
-                height: abstract_texture.height,
-                depth_or_array_layers: 1,
-            },
-        );
+        });
+
+        // render targets have no pixels, they are drawn by the renderer
+        if !render_target {
+            Self::write_level(queue, &texture, 0, abstract_texture.width, abstract_texture.height, &abstract_texture.data);
+            if abstract_texture.mipmaps {
+                for (index, level) in generate_mipmaps(abstract_texture).iter().enumerate() {
+                    Self::write_level(queue, &texture, index as u32 + 1, level.width, level.height, &level.data);
+                }
+            }
+        }
 
         let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Nearest,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            ..Default::default()
-        });
 
         let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             layout: bind_group_layout,
@@ -72,7 +68,7 @@ impl WgpuTexture {
                 },
                 wgpu::BindGroupEntry {
                     binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&sampler),
+                    resource: wgpu::BindingResource::Sampler(sampler),
                 },
             ],
             label: Some(&format!("{}_bind_group", abstract_texture.name)),
@@ -81,8 +77,44 @@ impl WgpuTexture {
         Self {
             texture,
             view,
-            sampler,
             bind_group,
         }
     }
+
+    pub fn write_level(queue: &wgpu::Queue, texture: &wgpu::Texture, mip_level: u32, width: u32, height: u32, data: &[u8]) {
+        Self::write_region(queue, texture, mip_level, [0, 0], [width, height], data);
+    }
+
+    /// Copies tightly packed RGBA rows into a rectangle of a mip level. The queue stages the pixels itself
+    /// and uploads them with the next submit, so rows don't need padding to the alignment of buffer copies.
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - The corner of the rectangle with the lowest coordinates in the stored texture
+    /// * `size` - Width and height of the rectangle in pixels
+    pub fn write_region(queue: &wgpu::Queue, texture: &wgpu::Texture, mip_level: u32, origin: [u32; 2], size: [u32; 2], data: &[u8]) {
+        let [width, height] = size;
+        if width == 0 || height == 0 {
+            return;
+        }
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture,
+                mip_level,
+                origin: wgpu::Origin3d { x: origin[0], y: origin[1], z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            data,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(Self::RGBA_BYTES * width),
+                rows_per_image: Some(height),
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
 }
@@ -0,0 +1,315 @@
+use crate::engine::renderer::material::{ColorSpace, SamplerOptions, Texture, TextureKind};
+use crate::engine::renderer::texture_region::TextureRegion;
+
+const BYTES_PER_PIXEL: usize = 4;
+
+#[derive(Debug, Clone, Copy)]
+pub struct AtlasPackerOptions {
+    /// The size of every atlas page in pixels, images are spread over as many pages as needed.
+    pub page_size: [u32; 2],
+    /// Empty pixels between the images and along the page border.
+    pub padding: u32,
+    /// How many times the border pixels of each image are repeated around it, so that filtering
+    /// near the edge of a region samples the image's own colours instead of its neighbours'.
+    pub extrude: u32,
+    /// How the pages are sampled, e.g. `SamplerOptions::PIXEL_ART` for pixel art.
+    pub sampler: SamplerOptions,
+    /// Whether the pages get mipmaps, which needs enough padding to keep smaller levels from mixing neighbours.
+    pub mipmaps: bool,
+    /// The colour space of the packed textures, which all need to share it.
+    pub color_space: ColorSpace
+}
+
+impl Default for AtlasPackerOptions {
+    fn default() -> Self {
+        Self {
+            page_size: [2048, 2048],
+            padding: 2,
+            extrude: 1,
+            sampler: SamplerOptions::default(),
+            mipmaps: false,
+            color_space: ColorSpace::Srgb
+        }
+    }
+}
+
+/// Textures merged into one or more atlas pages, so that they can be drawn without switching textures.
+#[derive(Debug, Clone)]
+pub struct PackedAtlas {
+    pub pages: Vec<Texture>,
+    pub regions: Vec<PackedRegion> // in the order of the packed textures
+}
+
+/// Where a packed texture ended up.
+#[derive(Debug, Clone)]
+pub struct PackedRegion {
+    pub name: String, // the name of the packed texture
+    pub page: usize, // index into the pages
+    pub region: TextureRegion
+}
+
+impl PackedAtlas {
+
+    /// The page and region of a packed texture, by its name.
+    pub fn region(&self, name: &str) -> Option<(&Texture, TextureRegion)> {
+        self.regions.iter()
+            .find(|packed| packed.name == name)
+            .map(|packed| (&self.pages[packed.page], packed.region))
+    }
+}
+
+/// Packs textures into atlas pages, row by row with the tallest textures first.
+///
+/// # Arguments
+///
+/// * `name` - Prefix of the page texture names, which are numbered from 0
+/// * `textures` - The textures to pack, identified by their names in the result
+/// * `options` - Page size, padding, extrusion and sampling of the pages
+///
+/// # Returns
+///
+/// * `Result<PackedAtlas, String>` - The pages and regions, or an error if a texture doesn't fit on a page
+///   or has no pixel data to copy, like render targets.
+pub fn pack_textures(name: &str, textures: &[Texture], options: AtlasPackerOptions) -> Result<PackedAtlas, String> {
+    for texture in textures {
+        if texture.kind != TextureKind::Pixels {
+            return Err(format!("Texture {} is a {:?} without pixels and can't be packed", texture.name, texture.kind));
+        }
+        let expected_bytes = texture.width as usize * texture.height as usize * BYTES_PER_PIXEL;
+        if texture.data.len() != expected_bytes {
+            return Err(format!("Texture {} of {}x{} has {} bytes instead of {}",
+                texture.name, texture.width, texture.height, texture.data.len(), expected_bytes));
+        }
+    }
+
+    let [page_width, page_height] = options.page_size;
+    let border = options.extrude * 2;
+
+    let mut order: Vec<usize> = (0..textures.len()).collect();
+    order.sort_by_key(|index| std::cmp::Reverse(textures[*index].height));
+
+    // positions of the extruded cells, with the origin at the top left of the page
+    let mut placements: Vec<Option<(usize, u32, u32)>> = vec![None; textures.len()];
+    let mut page_count = 0;
+    let mut shelf = Shelf::new(options.padding);
+    for index in order {
+        let texture = &textures[index];
+        let cell_width = texture.width + border;
+        let cell_height = texture.height + border;
+        if cell_width + options.padding * 2 > page_width || cell_height + options.padding * 2 > page_height {
+            return Err(format!("Texture {} of {}x{} doesn't fit on an atlas page of {}x{}",
+                texture.name, texture.width, texture.height, page_width, page_height));
+        }
+
+        if page_count == 0 {
+            page_count = 1;
+        }
+        if shelf.x + cell_width + options.padding > page_width {
+            shelf.next_row(options.padding);
+        }
+        if shelf.y + cell_height + options.padding > page_height {
+            page_count += 1;
+            shelf = Shelf::new(options.padding);
+        }
+
+        placements[index] = Some((page_count - 1, shelf.x, shelf.y));
+        shelf.x += cell_width + options.padding;
+        shelf.height = shelf.height.max(cell_height);
+    }
+
+    let mut pages: Vec<Texture> = (0..page_count)
+        .map(|page| Texture {
+            sampler: options.sampler,
+            mipmaps: options.mipmaps,
+            color_space: options.color_space,
+            ..Texture::from_pixels(
+                format!("{}_{}", name, page),
+                page_width,
+                page_height,
+                vec![0; page_width as usize * page_height as usize * BYTES_PER_PIXEL]
+            )
+        })
+        .collect();
+
+    let regions = textures.iter()
+        .zip(placements)
+        .map(|(texture, placement)| {
+            let (page, cell_x, cell_y) = placement.unwrap();
+            copy_extruded(texture, &mut pages[page], cell_x, cell_y, options.extrude);
+            PackedRegion {
+                name: texture.name.clone(),
+                page,
+                region: TextureRegion {
+                    x: cell_x + options.extrude,
+                    y: cell_y + options.extrude,
+                    width: texture.width,
+                    height: texture.height,
+                    texture_size: options.page_size
+                }
+            }
+        })
+        .collect();
+
+    Ok(PackedAtlas {
+        pages,
+        regions
+    })
+}
+
+/// The row of the page that textures are currently placed in.
+struct Shelf {
+    x: u32,
+    y: u32,
+    height: u32
+}
+
+impl Shelf {
+    fn new(padding: u32) -> Self {
+        Self { x: padding, y: padding, height: 0 }
+    }
+
+    fn next_row(&mut self, padding: u32) {
+        self.x = padding;
+        self.y += self.height + padding;
+        self.height = 0;
+    }
+}
+
+/// Copies a texture into a page with its border pixels repeated around it. Both are stored bottom row first,
+/// the cell position has its origin at the top left.
+fn copy_extruded(texture: &Texture, page: &mut Texture, cell_x: u32, cell_y: u32, extrude: u32) {
+    if texture.width == 0 || texture.height == 0 {
+        return;
+    }
+
+    let extrude = extrude as i64;
+    for cell_row in 0..texture.height as i64 + extrude * 2 {
+        let source_row = (cell_row - extrude).clamp(0, texture.height as i64 - 1) as u32;
+        let page_row = cell_y + cell_row as u32;
+        for cell_column in 0..texture.width as i64 + extrude * 2 {
+            let source_column = (cell_column - extrude).clamp(0, texture.width as i64 - 1) as u32;
+            let page_column = cell_x + cell_column as u32;
+
+            let source = pixel_offset(texture, source_column, source_row);
+            let destination = pixel_offset(page, page_column, page_row);
+            page.data[destination..destination + BYTES_PER_PIXEL]
+                .copy_from_slice(&texture.data[source..source + BYTES_PER_PIXEL]);
+        }
+    }
+}
+
+/// The byte offset of a pixel, for a row counted from the top of a texture stored bottom row first.
+fn pixel_offset(texture: &Texture, column: u32, row_from_top: u32) -> usize {
+    let stored_row = texture.height - 1 - row_from_top;
+    (stored_row as usize * texture.width as usize + column as usize) * BYTES_PER_PIXEL
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::renderer::material::Texture;
+    use crate::engine::renderer::texture_region::TextureRegion;
+    use crate::engine::resources::atlas_packer::{pack_textures, pixel_offset, AtlasPackerOptions};
+
+    fn overlaps(a: &TextureRegion, b: &TextureRegion) -> bool {
+        a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height
+    }
+
+    #[test]
+    fn pack_textures_should_place_textures_without_overlap_and_copy_their_pixels() {
+        // given
+        let textures = vec![
+            Texture::filled("small", 4, 4, 10),
+            Texture::filled("tall", 4, 12, 20),
+            Texture::filled("wide", 12, 4, 30)
+        ];
+        let options = AtlasPackerOptions { page_size: [32, 32], padding: 1, extrude: 1, ..AtlasPackerOptions::default() };
+
+        // when
+        let atlas = pack_textures("atlas", &textures, options).unwrap();
+
+        // then
+        assert_eq!(atlas.pages.len(), 1);
+        for (index, a) in atlas.regions.iter().enumerate() {
+            for b in atlas.regions.iter().skip(index + 1) {
+                assert!(!overlaps(&a.region, &b.region), "{} overlaps {}", a.name, b.name);
+            }
+        }
+        let (page, wide) = atlas.region("wide").unwrap();
+        assert_eq!([wide.width, wide.height], [12, 4]);
+        assert_eq!(page.data[pixel_offset(page, wide.x, wide.y)], 30);
+    }
+
+    #[test]
+    fn pack_textures_should_extrude_border_pixels_into_the_padding() {
+        // given: a texture with a distinct top row
+        let mut texture = Texture::filled("sprite", 2, 2, 0);
+        let top_left = pixel_offset(&texture, 0, 0);
+        texture.data[top_left..top_left + 4].copy_from_slice(&[255, 0, 0, 255]);
+        let options = AtlasPackerOptions { page_size: [16, 16], padding: 2, extrude: 2, ..AtlasPackerOptions::default() };
+
+        // when
+        let atlas = pack_textures("atlas", &[texture], options).unwrap();
+
+        // then: the pixel is repeated up to two pixels above and left of the region
+        let (page, region) = atlas.region("sprite").unwrap();
+        assert_eq!([region.x, region.y], [4, 4]);
+        let corner = pixel_offset(page, region.x - 2, region.y - 2);
+        assert_eq!(page.data[corner..corner + 4], [255, 0, 0, 255]);
+        let outside = pixel_offset(page, region.x - 3, region.y - 3);
+        assert_eq!(page.data[outside..outside + 4], [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn pack_textures_should_start_new_pages_when_full() {
+        // given: four textures of which only two fit on a page
+        let textures: Vec<Texture> = (0..4)
+            .map(|index| Texture::filled(&format!("tile_{}", index), 7, 14, 1))
+            .collect();
+        let options = AtlasPackerOptions { page_size: [16, 16], padding: 0, extrude: 0, ..AtlasPackerOptions::default() };
+
+        // when
+        let atlas = pack_textures("atlas", &textures, options).unwrap();
+
+        // then
+        assert_eq!(atlas.pages.len(), 2);
+        assert_eq!(atlas.pages[1].name, "atlas_1");
+        assert_eq!(atlas.regions[3].page, 1);
+    }
+
+    #[test]
+    fn pack_textures_should_reject_textures_larger_than_a_page() {
+        // given
+        let textures = vec![Texture::filled("huge", 64, 8, 1)];
+
+        // when
+        let result = pack_textures("atlas", &textures, AtlasPackerOptions { page_size: [32, 32], ..AtlasPackerOptions::default() });
+
+        // then
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn pack_textures_should_reject_textures_with_the_wrong_amount_of_data() {
+        // given: a texture missing its last row
+        let mut texture = Texture::filled("short", 4, 4, 1);
+        texture.data.truncate(4 * 3 * 4);
+
+        // when
+        let result = pack_textures("atlas", &[texture], AtlasPackerOptions::default());
+
+        // then
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn pack_textures_should_reject_render_targets() {
+        // given
+        let textures = vec![Texture::filled("sprite", 4, 4, 1), Texture::render_target(String::from("minimap"), 4, 4)];
+
+        // when
+        let result = pack_textures("atlas", &textures, AtlasPackerOptions::default());
+
+        // then
+        assert!(result.is_err());
+    }
+}
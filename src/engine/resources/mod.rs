@@ -0,0 +1,4 @@
+pub mod texture_loader;
+pub mod shader_loader;
+pub mod sprite_sheet;
+pub mod atlas_packer;
@@ -0,0 +1,14 @@
+use std::path::PathBuf;
+use crate::engine::renderer::material::ShaderDefinition;
+
+/// Loads a WGSL shader for materials or post-processing effects at runtime.
+/// In debug builds the renderer watches the file and recompiles the shader when it changes.
+pub fn load_shader_from_file(path: &str, name: String) -> Result<ShaderDefinition, Box<dyn std::error::Error>> {
+    let source = std::fs::read_to_string(path)
+        .map_err(|error| format!("Failed to read shader {} from {}: {}", name, path, error))?;
+    Ok(ShaderDefinition {
+        name,
+        source,
+        path: Some(PathBuf::from(path))
+    })
+}
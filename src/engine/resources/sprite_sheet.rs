@@ -0,0 +1,268 @@
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use serde::Deserialize;
+use crate::engine::renderer::animation::{AnimationClip, AnimationFrame, PlaybackMode};
+use crate::engine::renderer::material::Texture;
+use crate::engine::renderer::texture_region::TextureRegion;
+use crate::engine::resources::texture_loader::load_texture_from_file;
+
+/// Frame duration for sheets that don't specify one, such as TexturePacker exports.
+const DEFAULT_FRAME_DURATION: Duration = Duration::from_millis(100);
+
+/// An atlas texture with its named frames, and the animations tagged in Aseprite.
+#[derive(Debug, Clone)]
+pub struct SpriteSheet {
+    pub texture: Texture,
+    pub frames: Vec<SpriteSheetFrame>, // in the order of the file
+    pub clips: Vec<AnimationClip>
+}
+
+#[derive(Debug, Clone)]
+pub struct SpriteSheetFrame {
+    pub name: String,
+    pub region: TextureRegion,
+    pub duration: Duration
+}
+
+impl SpriteSheet {
+
+    /// Reads the frames and animation tags of a sprite sheet from its JSON description, for an already loaded texture.
+    /// Supports the JSON hash and array formats of both Aseprite and TexturePacker.
+    pub fn from_json(json: &str, texture: Texture) -> Result<SpriteSheet, Box<dyn Error>> {
+        let sheet: SheetJson = serde_json::from_str(json)?;
+        SpriteSheet::from_sheet_json(sheet, texture)
+    }
+
+    pub fn region(&self, frame_name: &str) -> Option<TextureRegion> {
+        self.frames.iter().find(|frame| frame.name == frame_name).map(|frame| frame.region)
+    }
+
+    pub fn clip(&self, clip_name: &str) -> Option<&AnimationClip> {
+        self.clips.iter().find(|clip| clip.name == clip_name)
+    }
+
+    fn from_sheet_json(sheet: SheetJson, texture: Texture) -> Result<SpriteSheet, Box<dyn Error>> {
+        let texture_size = [texture.width, texture.height];
+        let named_frames = match sheet.frames {
+            FramesJson::Array(frames) => frames.into_iter()
+                .map(|frame| (frame.filename, frame.frame))
+                .collect::<Vec<_>>(),
+            FramesJson::Hash(frames) => frames.into_iter()
+                .map(|(name, frame)| serde_json::from_value(frame).map(|frame| (name, frame)))
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        let frames = named_frames.into_iter()
+            .map(|(name, frame)| {
+                if frame.rotated {
+                    return Err(format!("Frame {} is rotated, disable rotation in the export settings", name));
+                }
+                let FrameRectJson { x, y, w, h } = frame.frame;
+                if x.saturating_add(w) > texture_size[0] || y.saturating_add(h) > texture_size[1] {
+                    return Err(format!("Frame {} lies outside of the {}x{} texture", name, texture_size[0], texture_size[1]));
+                }
+                Ok(SpriteSheetFrame {
+                    name,
+                    region: TextureRegion { x, y, width: w, height: h, texture_size },
+                    duration: frame.duration.map_or(DEFAULT_FRAME_DURATION, Duration::from_millis)
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let clips = sheet.meta.frame_tags.iter()
+            .map(|tag| clip_from_tag(tag, &frames))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(SpriteSheet {
+            texture,
+            frames,
+            clips
+        })
+    }
+}
+
+/// Loads a sprite sheet exported by Aseprite or TexturePacker: the JSON file and the image it refers to,
+/// which is looked up relative to the JSON file.
+pub fn load_sprite_sheet(json_path: &str) -> Result<SpriteSheet, Box<dyn Error>> {
+    let json = fs::read_to_string(json_path)?;
+    let sheet: SheetJson = serde_json::from_str(&json)?;
+
+    let image_path = Path::new(json_path)
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(&sheet.meta.image);
+    let image_path = image_path.to_str()
+        .ok_or_else(|| format!("Image path {:?} is not valid UTF-8", image_path))?;
+    let texture = load_texture_from_file(image_path, sheet.meta.image.clone())?;
+
+    SpriteSheet::from_sheet_json(sheet, texture)
+}
+
+fn clip_from_tag(tag: &FrameTagJson, frames: &[SpriteSheetFrame]) -> Result<AnimationClip, String> {
+    if tag.from > tag.to || tag.to >= frames.len() {
+        return Err(format!("Tag {} refers to frames {} to {}, but there are {} frames", tag.name, tag.from, tag.to, frames.len()));
+    }
+
+    let mut clip_frames: Vec<AnimationFrame> = frames[tag.from..=tag.to].iter()
+        .map(|frame| AnimationFrame { region: frame.region, duration: frame.duration })
+        .collect();
+    let mode = match tag.direction.as_str() {
+        "forward" => PlaybackMode::Loop,
+        "reverse" => {
+            clip_frames.reverse();
+            PlaybackMode::Loop
+        },
+        "pingpong" => PlaybackMode::PingPong,
+        "pingpong_reverse" => {
+            clip_frames.reverse();
+            PlaybackMode::PingPong
+        },
+        direction => return Err(format!("Tag {} has unknown direction {}", tag.name, direction))
+    };
+
+    Ok(AnimationClip {
+        name: tag.name.clone(),
+        frames: clip_frames,
+        mode
+    })
+}
+
+#[derive(Deserialize)]
+struct SheetJson {
+    frames: FramesJson,
+    meta: MetaJson
+}
+
+/// The hash format maps frame names to frames, the array format lists frames with their names.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FramesJson {
+    Hash(serde_json::Map<String, serde_json::Value>),
+    Array(Vec<NamedFrameJson>)
+}
+
+#[derive(Deserialize)]
+struct NamedFrameJson {
+    filename: String,
+    #[serde(flatten)]
+    frame: FrameJson
+}
+
+#[derive(Deserialize)]
+struct FrameJson {
+    frame: FrameRectJson,
+    #[serde(default)]
+    rotated: bool,
+    duration: Option<u64> // milliseconds, only exported by Aseprite
+}
+
+#[derive(Deserialize)]
+struct FrameRectJson {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32
+}
+
+#[derive(Deserialize)]
+struct MetaJson {
+    image: String,
+    #[serde(default, rename = "frameTags")]
+    frame_tags: Vec<FrameTagJson> // only exported by Aseprite
+}
+
+#[derive(Deserialize)]
+struct FrameTagJson {
+    name: String,
+    from: usize,
+    to: usize,
+    #[serde(default = "forward_direction")]
+    direction: String
+}
+
+fn forward_direction() -> String {
+    String::from("forward")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use crate::engine::renderer::animation::PlaybackMode;
+    use crate::engine::renderer::material::Texture;
+    use crate::engine::renderer::texture_region::TextureRegion;
+    use crate::engine::resources::sprite_sheet::SpriteSheet;
+
+    #[test]
+    fn sprite_sheet_should_read_aseprite_hash_with_tags_and_durations() {
+        // given
+        let json = r#"{
+            "frames": {
+                "knight 0.aseprite": { "frame": { "x": 0, "y": 0, "w": 16, "h": 16 }, "rotated": false, "trimmed": false, "duration": 100 },
+                "knight 1.aseprite": { "frame": { "x": 16, "y": 0, "w": 16, "h": 16 }, "rotated": false, "trimmed": false, "duration": 200 },
+                "knight 2.aseprite": { "frame": { "x": 32, "y": 0, "w": 16, "h": 16 }, "rotated": false, "trimmed": false, "duration": 300 }
+            },
+            "meta": {
+                "app": "https://www.aseprite.org/",
+                "image": "knight.png",
+                "size": { "w": 48, "h": 16 },
+                "frameTags": [
+                    { "name": "idle", "from": 0, "to": 0, "direction": "forward" },
+                    { "name": "walk", "from": 1, "to": 2, "direction": "pingpong" }
+                ]
+            }
+        }"#;
+
+        // when
+        let sheet = SpriteSheet::from_json(json, Texture::filled("sheet.png", 48, 16, 0)).unwrap();
+
+        // then
+        assert_eq!(sheet.frames.len(), 3);
+        assert_eq!(sheet.frames[2].name, "knight 2.aseprite");
+        assert_eq!(sheet.region("knight 1.aseprite"), Some(TextureRegion { x: 16, y: 0, width: 16, height: 16, texture_size: [48, 16] }));
+        let walk = sheet.clip("walk").unwrap();
+        assert_eq!(walk.mode, PlaybackMode::PingPong);
+        assert_eq!(walk.frames.len(), 2);
+        assert_eq!(walk.frames[1].duration, Duration::from_millis(300));
+        assert_eq!(walk.frames[1].region.x, 32);
+    }
+
+    #[test]
+    fn sprite_sheet_should_read_texture_packer_array() {
+        // given
+        let json = r#"{
+            "frames": [
+                { "filename": "coin.png", "frame": { "x": 0, "y": 0, "w": 8, "h": 8 }, "rotated": false, "trimmed": false,
+                  "spriteSourceSize": { "x": 0, "y": 0, "w": 8, "h": 8 }, "sourceSize": { "w": 8, "h": 8 } },
+                { "filename": "gem.png", "frame": { "x": 8, "y": 0, "w": 8, "h": 16 }, "rotated": false, "trimmed": false,
+                  "spriteSourceSize": { "x": 0, "y": 0, "w": 8, "h": 16 }, "sourceSize": { "w": 8, "h": 16 } }
+            ],
+            "meta": { "app": "https://www.codeandweb.com/texturepacker", "image": "items.png", "format": "RGBA8888", "size": { "w": 16, "h": 16 } }
+        }"#;
+
+        // when
+        let sheet = SpriteSheet::from_json(json, Texture::filled("sheet.png", 16, 16, 0)).unwrap();
+
+        // then
+        assert_eq!(sheet.region("gem.png"), Some(TextureRegion { x: 8, y: 0, width: 8, height: 16, texture_size: [16, 16] }));
+        assert!(sheet.clips.is_empty());
+    }
+
+    #[test]
+    fn sprite_sheet_should_reject_rotated_frames_and_invalid_tags() {
+        // given
+        let rotated = r#"{
+            "frames": { "coin.png": { "frame": { "x": 0, "y": 0, "w": 8, "h": 8 }, "rotated": true } },
+            "meta": { "image": "items.png" }
+        }"#;
+        let invalid_tag = r#"{
+            "frames": { "coin.png": { "frame": { "x": 0, "y": 0, "w": 8, "h": 8 } } },
+            "meta": { "image": "items.png", "frameTags": [ { "name": "spin", "from": 0, "to": 3 } ] }
+        }"#;
+
+        // then
+        assert!(SpriteSheet::from_json(rotated, Texture::filled("sheet.png", 16, 16, 0)).is_err());
+        assert!(SpriteSheet::from_json(invalid_tag, Texture::filled("sheet.png", 16, 16, 0)).is_err());
+    }
+}
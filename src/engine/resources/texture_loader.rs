@@ -0,0 +1,24 @@
+use crate::engine::renderer::material::Texture;
+use crate::engine::renderer::texture_region::{slice_grid, TextureRegion};
+
+pub fn load_texture_from_file(path: &str, name: String) -> Result<Texture, Box<dyn std::error::Error>> {
+    let img = image::open(path)?;
+
+    // Flip the image vertically so that its 0,0 coordinate is at the bottom left. 
+    // Convert to RGBA8 format (4 bytes per pixel)
+    let rgba = img.flipv().to_rgba8();
+
+    let width = rgba.width();
+    let height = rgba.height();
+    
+    let data: Vec<u8> = rgba.into_raw();
+
+    Ok(Texture::from_pixels(name, width, height, data))
+}
+
+/// Loads a sprite sheet as a single texture, sliced into cells of equal size row by row from the top left.
+pub fn load_sprite_grid(path: &str, name: String, cell_size: [u32; 2]) -> Result<(Texture, Vec<TextureRegion>), Box<dyn std::error::Error>> {
+    let texture = load_texture_from_file(path, name)?;
+    let regions = slice_grid(&texture, cell_size);
+    Ok((texture, regions))
+}
@@ -0,0 +1,227 @@
+use crate::engine::application::Application;
+use crate::engine::core::commands::ApplicationCommand;
+use crate::engine::core::frame_clock::FrameClock;
+use crate::engine::core::window::WindowId;
+use crate::engine::events::ApplicationEvent::WindowClosed;
+use crate::engine::events::Event;
+use crate::engine::runners::Runner;
+use log::{debug, info};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How the headless frame loop advances time.
+#[derive(Debug, Clone, Copy)]
+pub enum HeadlessClock {
+    /// Frames are paced to the wall clock, layers receive the measured frame time.
+    RealTime {
+        frames_per_second: u32
+    },
+    /// Frames run back to back, layers receive a fixed timestep. Useful for deterministic tests.
+    AsFastAsPossible {
+        timestep: Duration
+    }
+}
+
+/// Runs the application without windows or GPU, e.g. for dedicated servers and simulation tests.
+/// Layers are updated every frame but never rendered. Events can be injected through
+/// `Application::event_sender`, and the loop stops when the main window receives `WindowClosed`,
+/// when it is closed through a command, or after `max_frames` frames.
+#[derive(Debug, Clone)]
+pub struct HeadlessRunner {
+    pub clock: HeadlessClock,
+    pub max_frames: Option<u64>
+}
+
+impl HeadlessRunner {
+
+    /// Returns whether the loop should keep running.
+    fn process_commands(&self, app: &mut Application) -> bool {
+        let mut running = true;
+        while let Some(command) = app.receive_command() {
+            match command {
+                ApplicationCommand::OpenWindow { layerstack, .. } => {
+                    app.add_layerstack(layerstack);
+                },
+                ApplicationCommand::CloseWindow { window_id } if window_id == WindowId::MAIN => running = false,
+                ApplicationCommand::CloseWindow { window_id } => app.remove_layerstack(window_id),
+                command => debug!("Command {:?} has no effect without a window", command)
+            }
+        }
+        running
+    }
+
+    /// Returns whether the loop should keep running.
+    fn process_events(&self, app: &mut Application) -> bool {
+        let mut running = true;
+        while let Some((window_id, event)) = app.receive_event() {
+            app.dispatch_event(window_id, &event);
+
+            if let Event::ApplicationEvent(WindowClosed) = event {
+                if window_id == WindowId::MAIN {
+                    running = false;
+                } else {
+                    app.remove_layerstack(window_id);
+                }
+            }
+        }
+        running
+    }
+}
+
+impl Runner for HeadlessRunner {
+    fn run(self, app: &mut Application) {
+        info!("Engine started headless");
+
+        let mut frame_clock = FrameClock::new();
+        let mut frame_count: u64 = 0;
+        while self.max_frames.is_none_or(|max_frames| frame_count < max_frames) {
+            let frame_start = Instant::now();
+
+            let commands_running = self.process_commands(app);
+            let events_running = self.process_events(app);
+            if !commands_running || !events_running {
+                break;
+            }
+
+            let delta_time = match self.clock {
+                HeadlessClock::RealTime { .. } => frame_clock.tick(),
+                HeadlessClock::AsFastAsPossible { timestep } => timestep
+            };
+            for window_id in app.window_ids() {
+                app.update_layers(window_id, delta_time);
+            }
+            frame_count += 1;
+
+            if let HeadlessClock::RealTime { frames_per_second } = self.clock {
+                let frame_duration = Duration::from_secs_f64(1.0 / frames_per_second.max(1) as f64);
+                thread::sleep(frame_duration.saturating_sub(frame_start.elapsed()));
+            }
+        }
+
+        info!("Engine stopped after {} frames", frame_count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::core::key_codes::KeyCode;
+    use crate::engine::core::layer::Layer;
+    use crate::engine::core::layerstack::LayerStack;
+    use crate::engine::core::window::WindowProps;
+    use crate::engine::events::KeyboardEvent::KeyPressed;
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+
+    #[test]
+    fn headless_runner_should_update_layers_with_fixed_timestep() {
+        // given
+        let mut app = Application::new(WindowProps::default());
+        let layer = LayerStub::new();
+        let elapsed = Rc::clone(&layer.elapsed);
+        app.push_layer(Box::new(layer));
+        let runner = HeadlessRunner {
+            clock: HeadlessClock::AsFastAsPossible { timestep: Duration::from_millis(10) },
+            max_frames: Some(3)
+        };
+
+        // when
+        app.run_with(runner);
+
+        // then
+        assert_eq!(elapsed.get(), Duration::from_millis(30));
+    }
+
+    #[test]
+    fn headless_runner_should_deliver_injected_events_and_stop_when_main_window_closes() {
+        // given
+        let mut app = Application::new(WindowProps::default());
+        let layer = LayerStub::new();
+        let elapsed = Rc::clone(&layer.elapsed);
+        let events = Rc::clone(&layer.events);
+        app.push_layer(Box::new(layer));
+        let event_sender = app.event_sender();
+        event_sender.send((WindowId::MAIN, Event::KeyboardEvent(KeyPressed { key_code: KeyCode::Space, is_repeat: false }))).unwrap();
+        event_sender.send((WindowId::MAIN, Event::ApplicationEvent(WindowClosed))).unwrap();
+        let runner = HeadlessRunner {
+            clock: HeadlessClock::AsFastAsPossible { timestep: Duration::from_millis(10) },
+            max_frames: None
+        };
+
+        // when
+        app.run_with(runner);
+
+        // then: both events arrived, but no frame was run after the window closed
+        assert_eq!(events.borrow().len(), 2);
+        assert_eq!(elapsed.get(), Duration::ZERO);
+    }
+
+    #[test]
+    fn headless_runner_should_route_events_to_the_layers_of_their_window() {
+        // given: a second window opened through a command, which gets the next window id
+        let mut app = Application::new(WindowProps::default());
+        let main_layer = LayerStub::new();
+        let main_keys = Rc::clone(&main_layer.pressed_keys);
+        app.push_layer(Box::new(main_layer));
+        let second_layer = LayerStub::new();
+        let second_keys = Rc::clone(&second_layer.pressed_keys);
+        let second_elapsed = Rc::clone(&second_layer.elapsed);
+        let mut second_layerstack = LayerStack::new();
+        second_layerstack.push_layer(Box::new(second_layer));
+        app.command_sender().send(ApplicationCommand::OpenWindow {
+            props: WindowProps::default(),
+            layerstack: second_layerstack
+        }).unwrap();
+        let second_window = WindowId(1);
+        let event_sender = app.event_sender();
+        event_sender.send((WindowId::MAIN, Event::KeyboardEvent(KeyPressed { key_code: KeyCode::A, is_repeat: false }))).unwrap();
+        event_sender.send((second_window, Event::KeyboardEvent(KeyPressed { key_code: KeyCode::B, is_repeat: false }))).unwrap();
+        event_sender.send((second_window, Event::KeyboardEvent(KeyPressed { key_code: KeyCode::C, is_repeat: false }))).unwrap();
+        let runner = HeadlessRunner {
+            clock: HeadlessClock::AsFastAsPossible { timestep: Duration::from_millis(10) },
+            max_frames: Some(2)
+        };
+
+        // when
+        app.run_with(runner);
+
+        // then: each window's layers only saw their own events, and the second window's layers were updated as well
+        assert_eq!(*main_keys.borrow(), vec![KeyCode::A]);
+        assert_eq!(*second_keys.borrow(), vec![KeyCode::B, KeyCode::C]);
+        assert_eq!(second_elapsed.get(), Duration::from_millis(20));
+    }
+
+    struct LayerStub {
+        elapsed: Rc<Cell<Duration>>,
+        events: Rc<RefCell<Vec<String>>>,
+        pressed_keys: Rc<RefCell<Vec<KeyCode>>>
+    }
+
+    impl LayerStub {
+        fn new() -> Self {
+            Self {
+                elapsed: Rc::new(Cell::new(Duration::ZERO)),
+                events: Rc::new(RefCell::new(Vec::new())),
+                pressed_keys: Rc::new(RefCell::new(Vec::new()))
+            }
+        }
+    }
+
+    impl Layer for LayerStub {
+        fn update(&self, delta_time: Duration) {
+            self.elapsed.set(self.elapsed.get() + delta_time);
+        }
+
+        fn handle_event(&self, event: &Event) -> bool {
+            self.events.borrow_mut().push(format!("{:?}", event));
+            if let Event::KeyboardEvent(KeyPressed { key_code, .. }) = event {
+                self.pressed_keys.borrow_mut().push(*key_code);
+            }
+            true
+        }
+
+        fn get_name(&self) -> &str {
+            "Layer Stub"
+        }
+    }
+}
@@ -0,0 +1,9 @@
+pub mod winit_runner;
+pub mod headless_runner;
+
+use crate::engine::application::Application;
+
+/// Drives an application's frame loop: delivers its events, updates its layers and renders them.
+pub trait Runner {
+    fn run(self, app: &mut Application);
+}
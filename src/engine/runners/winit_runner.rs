@@ -0,0 +1,361 @@
+use crate::engine::application::Application;
+use crate::engine::core::commands::ApplicationCommand;
+use crate::engine::core::cursor::{CursorGrabMode, CustomCursor};
+use crate::engine::core::frame_clock::FrameClock;
+use crate::engine::core::window::{WindowId, WindowProps};
+use crate::engine::events::ApplicationEvent::{RenderRequested, ScaleFactorChanged, WindowClosed, WindowFocused, WindowOccluded, WindowResized};
+use crate::engine::events::Event;
+use crate::engine::events::winit_event_mapper::{map_device_event, map_event};
+use crate::engine::events::winit_input_mapper::WinitWindowId;
+use crate::engine::renderer::Renderer;
+use crate::engine::renderer::texture_region::TextureRegion;
+use crate::engine::renderer::wgpu::wgpu_renderer::WgpuRenderer;
+use crate::engine::runners::Runner;
+use log::{debug, info, warn};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+use winit::application::ApplicationHandler;
+use winit::dpi::LogicalSize;
+use winit::event::{DeviceEvent, DeviceId, WindowEvent};
+use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
+use winit::window::{Cursor, CursorIcon, CustomCursorSource};
+
+type WinitWindow = winit::window::Window;
+
+/// Runs the application in OS windows, rendered with wgpu.
+pub struct WinitRunner;
+
+impl Runner for WinitRunner {
+    fn run(self, app: &mut Application) {
+        info!("Engine started");
+
+        let event_loop = EventLoop::new().unwrap();
+        event_loop.set_control_flow(ControlFlow::Wait);
+        event_loop.run_app(&mut WinitApplicationHandler {
+            app,
+            windows: HashMap::new(),
+            window_ids: HashMap::new()
+        });
+    }
+}
+
+struct WinitApplicationHandler<'a> {
+    app: &'a mut Application,
+    windows: HashMap<WindowId, WindowContext>,
+    window_ids: HashMap<WinitWindowId, WindowId>
+}
+
+/// Everything that belongs to a single open window: its surface, renderer and frame timing.
+struct WindowContext {
+    window: Arc<WinitWindow>,
+    renderer: WgpuRenderer<'static>,
+    cursor_grab_mode: CursorGrabMode,
+    frame_clock: FrameClock,
+    last_frame: Option<Instant>,
+    redraw_requested: bool,
+    focused: bool,
+    occluded: bool,
+    minimised: bool
+}
+
+impl WinitApplicationHandler<'_> {
+
+    fn open_window(&mut self, event_loop: &ActiveEventLoop, window_id: WindowId, props: &WindowProps) {
+        let window_attributes = WinitWindow::default_attributes()
+            .with_title(&props.title)
+            .with_inner_size(LogicalSize::new(
+                props.width,
+                props.height
+            ));
+        let window = event_loop.create_window(window_attributes).unwrap();
+        let window_rc = Arc::new(window);
+
+        // all windows render with the same GPU device, so textures and pipelines are compatible
+        let renderer = match self.windows.values().next() {
+            Some(context) => WgpuRenderer::with_shared_infra(Arc::clone(&window_rc), context.renderer.infra()),
+            None => WgpuRenderer::new(Arc::clone(&window_rc))
+        };
+
+        // let the window's layers know the initial surface, winit only reports changes
+        let size = window_rc.inner_size();
+        self.app.send_event(window_id, Event::ApplicationEvent(ScaleFactorChanged { scale_factor: window_rc.scale_factor() }));
+        self.app.send_event(window_id, Event::ApplicationEvent(WindowResized { width: size.width, height: size.height }));
+
+        info!("Window {:?} opened: {}", window_id, props.title);
+        self.window_ids.insert(window_rc.id(), window_id);
+        self.windows.insert(window_id, WindowContext {
+            window: window_rc,
+            renderer,
+            cursor_grab_mode: CursorGrabMode::None,
+            frame_clock: FrameClock::new(),
+            last_frame: None,
+            redraw_requested: false,
+            focused: true,
+            occluded: false,
+            minimised: false
+        });
+    }
+
+    fn close_window(&mut self, event_loop: &ActiveEventLoop, window_id: WindowId) {
+        if window_id == WindowId::MAIN {
+            event_loop.exit();
+        } else if let Some(context) = self.windows.remove(&window_id) {
+            self.window_ids.remove(&context.window.id());
+            self.app.remove_layerstack(window_id);
+            info!("Window {:?} closed", window_id);
+        }
+    }
+
+    fn target_window(&mut self, window_id: Option<WindowId>) -> Option<&mut WindowContext> {
+        self.windows.get_mut(&window_id.unwrap_or(WindowId::MAIN))
+    }
+
+    fn process_commands(&mut self, event_loop: &ActiveEventLoop) {
+        while let Some(command) = self.app.receive_command() {
+            match command {
+                ApplicationCommand::OpenWindow { props, layerstack } => {
+                    let window_id = self.app.add_layerstack(layerstack);
+                    self.open_window(event_loop, window_id, &props);
+                },
+                ApplicationCommand::CloseWindow { window_id } => self.close_window(event_loop, window_id),
+                ApplicationCommand::RequestRedraw { window_id } => {
+                    if let Some(context) = self.target_window(window_id) {
+                        context.redraw_requested = true;
+                    }
+                },
+                ApplicationCommand::SetCursorVisible { window_id, visible } => {
+                    if let Some(context) = self.target_window(window_id) {
+                        context.window.set_cursor_visible(visible);
+                    }
+                },
+                ApplicationCommand::SetCursorGrab { window_id, mode } => {
+                    if let Some(context) = self.target_window(window_id) {
+                        context.set_cursor_grab(mode);
+                    }
+                },
+                ApplicationCommand::SetCustomCursor { window_id, cursor } => {
+                    if let Some(context) = self.target_window(window_id) {
+                        context.set_custom_cursor(event_loop, &cursor);
+                    }
+                },
+                ApplicationCommand::ResetCursor { window_id } => {
+                    if let Some(context) = self.target_window(window_id) {
+                        context.window.set_cursor(Cursor::Icon(CursorIcon::Default));
+                    }
+                },
+                ApplicationCommand::SetPostProcessing { window_id, stack } => {
+                    if let Some(context) = self.target_window(window_id) {
+                        context.renderer.set_post_processing(stack);
+                    }
+                },
+                ApplicationCommand::SetTexturePixels { window_id, texture, region, pixels } => {
+                    if let Some(context) = self.target_window(window_id) {
+                        let region = region.unwrap_or_else(|| TextureRegion::full(&texture));
+                        if let Err(error) = context.renderer.update_texture_region(&texture, region, &pixels) {
+                            warn!("{}", error);
+                        }
+                    }
+                },
+                ApplicationCommand::UnloadTexture { window_id, texture } => {
+                    if let Some(context) = self.target_window(window_id) {
+                        context.renderer.unload_texture(&texture);
+                    }
+                },
+                ApplicationCommand::SetTextureMemoryBudget { window_id, budget } => {
+                    if let Some(context) = self.target_window(window_id) {
+                        context.renderer.set_texture_memory_budget(budget);
+                    }
+                }
+            }
+        }
+    }
+
+    fn process_events(&mut self, event_loop: &ActiveEventLoop) {
+        while let Some((window_id, event)) = self.app.receive_event() {
+            match event {
+                Event::ApplicationEvent(WindowResized { width, height }) => self.on_window_resized(window_id, width, height),
+                Event::ApplicationEvent(RenderRequested) => self.on_app_render(window_id),
+                Event::ApplicationEvent(WindowFocused { focused }) => {
+                    if let Some(context) = self.windows.get_mut(&window_id) {
+                        context.focused = focused;
+                    }
+                },
+                Event::ApplicationEvent(WindowOccluded { occluded }) => {
+                    if let Some(context) = self.windows.get_mut(&window_id) {
+                        context.occluded = occluded;
+                    }
+                },
+                _ => {
+                    // ignore for now
+                }
+            }
+
+            // in reactive mode, any input is a reason to render a new frame
+            if !matches!(event, Event::ApplicationEvent(RenderRequested)) {
+                if let Some(context) = self.windows.get_mut(&window_id) {
+                    context.redraw_requested = true;
+                }
+            }
+
+            self.app.dispatch_event(window_id, &event);
+
+            // closed last, so that the window's layers still receive the event
+            if let Event::ApplicationEvent(WindowClosed) = event {
+                self.close_window(event_loop, window_id);
+            }
+        }
+    }
+
+    fn on_app_render(&mut self, window_id: WindowId) {
+        if let Some(context) = self.windows.get_mut(&window_id) {
+            context.last_frame = Some(Instant::now());
+            context.redraw_requested = false;
+
+            let delta_time = context.frame_clock.tick();
+            self.app.update_layers(window_id, delta_time);
+            if !context.is_hidden() {
+                self.app.render_layers(window_id, &mut context.renderer);
+                debug!("Window {:?} frame: {:?}", window_id, context.renderer.stats());
+            }
+        }
+    }
+
+    fn on_window_resized(&mut self, window_id: WindowId, width: u32, height: u32) {
+        if let Some(context) = self.windows.get_mut(&window_id) {
+            // some platforms report minimising as a resize to zero
+            context.minimised = width == 0 || height == 0;
+            context.renderer.resize(width, height);
+        }
+    }
+
+    /// Starts the frames that are due and sleeps until the next one, following the application's frame pacing.
+    fn schedule_frames(&mut self, event_loop: &ActiveEventLoop) {
+        let frame_pacing = *self.app.frame_pacing();
+        let now = Instant::now();
+
+        let mut next_wake_up: Option<Instant> = None;
+        let mut due_window_ids = Vec::new();
+        for (window_id, context) in &self.windows {
+            let next_frame = frame_pacing.next_frame(
+                context.last_frame,
+                context.is_visible(),
+                context.redraw_requested,
+                now
+            );
+            match next_frame {
+                Some(next_frame) if next_frame <= now => due_window_ids.push(*window_id),
+                Some(next_frame) => next_wake_up = Some(next_wake_up.map_or(next_frame, |wake_up| wake_up.min(next_frame))),
+                None => {}
+            }
+        }
+
+        for window_id in due_window_ids {
+            let Some(context) = self.windows.get(&window_id) else {
+                continue;
+            };
+            if context.is_hidden() {
+                // hidden windows might never receive a redraw, so their layers are updated directly
+                self.on_app_render(window_id);
+            } else {
+                context.window.request_redraw();
+            }
+        }
+
+        match next_wake_up {
+            Some(wake_up) => event_loop.set_control_flow(ControlFlow::WaitUntil(wake_up)),
+            None => event_loop.set_control_flow(ControlFlow::Wait)
+        }
+    }
+}
+
+impl WindowContext {
+
+    fn is_hidden(&self) -> bool {
+        self.occluded || self.minimised
+    }
+
+    fn is_visible(&self) -> bool {
+        self.focused && !self.is_hidden()
+    }
+
+    /// Stores the mode that was actually applied, so that mouse movement keeps being reported
+    /// when the requested mode isn't supported.
+    fn set_cursor_grab(&mut self, mode: CursorGrabMode) {
+        let Err(error) = self.window.set_cursor_grab(mode.into()) else {
+            self.cursor_grab_mode = mode;
+            return;
+        };
+
+        // not every platform supports both modes, e.g. Windows can't lock and macOS can't confine
+        let fallback = match mode {
+            CursorGrabMode::Locked => CursorGrabMode::Confined,
+            CursorGrabMode::Confined => CursorGrabMode::Locked,
+            CursorGrabMode::None => CursorGrabMode::None
+        };
+        warn!("Cursor grab mode {:?} not supported ({}), falling back to {:?}", mode, error, fallback);
+        match self.window.set_cursor_grab(fallback.into()) {
+            Ok(()) => self.cursor_grab_mode = fallback,
+            // the previous mode is still in effect
+            Err(error) => warn!("Cursor grab mode {:?} not supported either: {}", fallback, error)
+        }
+    }
+
+    fn set_custom_cursor(&self, event_loop: &ActiveEventLoop, cursor: &CustomCursor) {
+        match CustomCursorSource::try_from(cursor) {
+            Ok(source) => self.window.set_cursor(event_loop.create_custom_cursor(source)),
+            Err(message) => warn!("{}", message)
+        }
+    }
+}
+
+impl ApplicationHandler for WinitApplicationHandler<'_> {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.windows.contains_key(&WindowId::MAIN) {
+            return;
+        }
+
+        let window_props = self.app.window_props().clone();
+        self.open_window(event_loop, WindowId::MAIN, &window_props);
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, id: WinitWindowId, event: WindowEvent) {
+        self.process_commands(event_loop);
+
+        let Some(window_id) = self.window_ids.get(&id).copied() else {
+            return;
+        };
+
+        // while locked, mouse movement is reported through the raw device events instead
+        let cursor_locked = self.windows.get(&window_id)
+            .is_some_and(|context| context.cursor_grab_mode == CursorGrabMode::Locked);
+        if cursor_locked && matches!(event, WindowEvent::CursorMoved { .. }) {
+            return;
+        }
+
+        if let Some(event) = map_event(event) {
+            self.app.send_event(window_id, event);
+        }
+        self.process_events(event_loop);
+    }
+
+    fn device_event(&mut self, event_loop: &ActiveEventLoop, device_id: DeviceId, event: DeviceEvent) {
+        let locked_window_id = self.windows.iter()
+            .find(|(_, context)| context.cursor_grab_mode == CursorGrabMode::Locked)
+            .map(|(window_id, _)| *window_id);
+        let Some(window_id) = locked_window_id else {
+            return;
+        };
+
+        if let Some(event) = map_device_event(event) {
+            self.app.send_event(window_id, event);
+        }
+        self.process_events(event_loop);
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        self.process_commands(event_loop);
+        self.process_events(event_loop);
+        self.schedule_frames(event_loop);
+    }
+}
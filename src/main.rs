@@ -1,6 +1,7 @@
 #![allow(dead_code,unused)]
 
 use buffengine::engine::application::Application;
+use buffengine::engine::core::frame_pacing::FramePacing;
 use buffengine::engine::core::window::WindowProps;
 use buffengine::logger;
 use buffengine::sample_game::debug_overlay::DebugOverlay;
@@ -11,13 +12,16 @@ use std::error::Error;
 fn main() -> Result<(), Box<dyn Error>> {
     init_logging()?;
 
-    let sample_layer = SceneLayer::new(String::from("Sample Layer"));
-    let debug_overlay = DebugOverlay {
-        name: String::from("Debug Overlay")
-    };
-
     let window_props = WindowProps::default();
     let mut app = Application::new(window_props);
+    app.set_frame_pacing(FramePacing {
+        target_fps: Some(60),
+        ..FramePacing::default()
+    });
+
+    let sample_layer = SceneLayer::new(String::from("Sample Layer"), app.command_sender());
+    let debug_overlay = DebugOverlay::new(String::from("Debug Overlay"), sample_layer.minimap_texture());
+
     app.push_layer(Box::new(sample_layer));
     app.push_overlay(Box::new(debug_overlay));
     app.run();
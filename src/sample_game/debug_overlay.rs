@@ -1,22 +1,103 @@
+use std::cell::{Ref, RefCell};
+use std::rc::Rc;
+use std::time::Duration;
 use log::debug;
 use crate::engine::core::layer::Layer;
+use crate::engine::events::ApplicationEvent::{ScaleFactorChanged, WindowResized};
 use crate::engine::events::Event;
+use crate::engine::renderer::{Renderable, Scene};
+use crate::engine::renderer::camera::{Anchor, Camera2D, ScreenUnits};
+use crate::engine::renderer::material::Texture;
+use crate::engine::renderer::texture_region::TextureRegion;
+use crate::sample_game::sprite::Sprite;
 
+const ICON_SIZE: f32 = 48.0; // logical pixels
+const ICON_MARGIN: f32 = 8.0; // logical pixels
+const MINIMAP_SIZE: f32 = 192.0; // logical pixels
+
+/// Draws icons in the corners of the window and a minimap below the top left icon,
+/// in screen space so that they stay put while the game camera moves.
 pub struct DebugOverlay {
-    pub name: String
+    pub name: String,
+    camera: RefCell<Camera2D>,
+    icon: Sprite,
+    minimap: Sprite
+}
+
+impl DebugOverlay {
+    /// # Parameters
+    /// * `minimap_texture` - the render target a minimap camera draws to
+    pub fn new(name: String, minimap_texture: Rc<Texture>) -> Self {
+        let minimap_region = TextureRegion::full(&minimap_texture);
+        let pixels_per_unit = minimap_texture.width as f32 / MINIMAP_SIZE;
+        Self {
+            name,
+            camera: RefCell::new(Camera2D::screen_space(ScreenUnits::Logical)),
+            icon: Sprite::new(0.0, 0.0, ICON_SIZE),
+            minimap: Sprite::from_region(minimap_texture, minimap_region, 0.0, 0.0, pixels_per_unit)
+        }
+    }
+
+    fn anchored_minimap(&self) -> Renderable {
+        let mut renderable = self.minimap.get_renderable().clone();
+        renderable.transform.position = self.camera.borrow().anchored_position(
+            Anchor::TopLeft,
+            [MINIMAP_SIZE, MINIMAP_SIZE],
+            [ICON_MARGIN, ICON_SIZE + ICON_MARGIN * 2.0]
+        );
+        renderable
+    }
+
+    fn anchored_icon(&self, anchor: Anchor) -> Renderable {
+        let mut renderable = self.icon.get_renderable().clone();
+        renderable.transform.position = self.camera.borrow().anchored_position(
+            anchor,
+            [ICON_SIZE, ICON_SIZE],
+            [ICON_MARGIN, ICON_MARGIN]
+        );
+        renderable
+    }
 }
 
 impl Layer for DebugOverlay {
-    fn update(&self) {
+    fn update(&self, delta_time: Duration) {
         debug!("DebugOverlay update");
     }
 
     fn handle_event(&self, event: &Event) -> bool {
         debug!("DebugOverlay event: {:?}", event);
+        // the layers below need these events as well, so they are not marked as handled
+        match event {
+            Event::ApplicationEvent(WindowResized { width, height }) => {
+                self.camera.borrow_mut().update_viewport_size([*width, *height]);
+            },
+            Event::ApplicationEvent(ScaleFactorChanged { scale_factor }) => {
+                self.camera.borrow_mut().update_scale_factor(*scale_factor);
+            },
+            _ => {}
+        }
         false
     }
 
     fn get_name(&self) -> &str {
         self.name.as_str()
     }
-}
\ No newline at end of file
+
+    fn as_scene(&self) -> Option<&dyn Scene> {
+        Some(self)
+    }
+}
+
+impl Scene for DebugOverlay {
+    fn get_renderables(&self) -> Vec<Renderable> {
+        vec![
+            self.anchored_icon(Anchor::TopLeft),
+            self.anchored_icon(Anchor::BottomRight),
+            self.anchored_minimap()
+        ]
+    }
+
+    fn get_camera(&self) -> Ref<'_, Camera2D> {
+        self.camera.borrow()
+    }
+}
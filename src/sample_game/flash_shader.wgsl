@@ -0,0 +1,55 @@
+// the sprite shader, flashing in a colour when the sprite is hit
+struct VertexData {
+    @location(0) position: vec3f,
+    @location(1) color: vec4f,
+    @location(2) tex_coords: vec2f,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) color: vec4f,
+    @location(1) tex_coords: vec2f,
+}
+
+struct Uniforms {
+    local_to_world_model_matrix: mat4x4<f32>,
+    world_to_view_matrix: mat4x4<f32>,
+    view_to_clip_matrix: mat4x4<f32>,
+    uv_rect: vec4f // offset in xy and size in zw
+}
+
+@group(1) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+// set through the material's params, see MaterialParams
+struct MaterialParams {
+    flash: f32, // 0 - 1, how much of the flash colour covers the sprite
+    flash_color: vec4f,
+}
+
+@group(2) @binding(0)
+var<uniform> material: MaterialParams;
+
+@vertex
+fn vertex_shader(vertex: VertexData) -> VertexOutput {
+    var world_space = uniforms.local_to_world_model_matrix * vec4f(vertex.position, 1.0);
+    var view_space = uniforms.world_to_view_matrix * world_space;
+
+    var output: VertexOutput;
+    output.clip_position = uniforms.view_to_clip_matrix * view_space;
+    output.color = vertex.color;
+    // map the whole mesh onto the texture region
+    output.tex_coords = uniforms.uv_rect.xy + vertex.tex_coords * uniforms.uv_rect.zw;
+    return output;
+}
+
+@group(0) @binding(0)
+var texture: texture_2d<f32>;
+@group(0) @binding(1)
+var texture_sampler: sampler;
+
+@fragment
+fn fragment_shader(vertex: VertexOutput) -> @location(0) vec4f {
+    let color = textureSample(texture, texture_sampler, vertex.tex_coords) * vertex.color;
+    return vec4f(mix(color.rgb, material.flash_color.rgb, material.flash), color.a);
+}
\ No newline at end of file
@@ -1,4 +1,3 @@
 pub mod scene_layer;
 pub mod debug_overlay;
-mod sprite;
-mod resource_loader;
\ No newline at end of file
+mod sprite;
\ No newline at end of file
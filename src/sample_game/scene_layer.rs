@@ -1,45 +1,241 @@
 use std::any::Any;
 use std::cell::{Cell, Ref, RefCell};
 use std::ops::{Deref, DerefMut};
-use log::debug;
+use std::rc::Rc;
+use std::sync::mpsc::Sender;
+use std::time::Duration;
+use log::{debug, info, warn};
+use crate::engine::core::commands::ApplicationCommand;
+use crate::engine::core::cursor::CursorGrabMode;
 use crate::engine::core::key_codes::KeyCode;
 use crate::engine::core::layer::Layer;
-use crate::engine::events::{ApplicationEvent, Event, KeyboardEvent};
-use crate::engine::events::ApplicationEvent::WindowResized;
+use crate::engine::core::mouse_codes::MouseCode;
+use crate::engine::events::{ApplicationEvent, Event, KeyboardEvent, MouseEvent};
+use crate::engine::events::ApplicationEvent::{ScaleFactorChanged, WindowResized};
 use crate::engine::renderer::{Renderable, Scene};
-use crate::engine::renderer::camera::Camera2D;
-use crate::sample_game::sprite::Sprite;
+use crate::engine::renderer::camera::{Camera2D, ClearPolicy, ScalingMode, ViewportRect};
+use crate::engine::renderer::material::{SamplerOptions, Texture};
+use crate::engine::renderer::material_params::MaterialValue;
+use crate::engine::renderer::post_processing::{PostEffect, PostProcessingStack};
+use crate::engine::renderer::animation::{AnimationClip, Animator, PlaybackMode};
+use crate::engine::renderer::texture_region::{slice_grid, TextureRegion};
+use crate::engine::renderer::transform::Transform2D;
+use crate::engine::renderer::transform_hierarchy::{TransformHierarchy, TransformId};
+use crate::engine::renderer::camera_controller::{BoundsController, CameraController, CameraShake, FollowController};
+use crate::sample_game::sprite::{flash_shader, Sprite};
 use crate::sample_game::sprite::Movable;
 
+const MINIMAP_TEXTURE_SIZE: u32 = 256; // pixels
+const TEXTURE_MEMORY_BUDGET: u64 = 64 * 1024 * 1024; // bytes
+const WORLD_MIN: f32 = -50.0; // world units, on both axes
+const WORLD_SIZE: u32 = 100; // world units, the explored map has a pixel per unit
+const UNEXPLORED_COLOR: [u8; 4] = [20, 20, 30, 255];
+const EXPLORED_COLOR: [u8; 4] = [60, 90, 60, 255];
+
 pub struct SceneLayer {
     pub name: String,
     player: RefCell<Sprite>,
+    player_animator: RefCell<Animator>,
+    player_flash: Cell<f32>, // 0 - 1, fades out after the player was hit
     npcs: Vec<Sprite>,
-    camera: RefCell<Camera2D>
+    orbiter: Sprite,
+    transforms: RefCell<TransformHierarchy>,
+    player_transform: TransformId,
+    orbit_transform: TransformId,
+    orbiter_transform: TransformId,
+    camera: RefCell<Camera2D>,
+    minimap_camera: RefCell<Camera2D>,
+    minimap_texture: Rc<Texture>,
+    explored_map: Sprite, // the ground, lit up where the player has been
+    explored_texture: Rc<Texture>,
+    camera_follow: RefCell<FollowController>,
+    camera_bounds: RefCell<BoundsController>,
+    camera_shake: RefCell<CameraShake>,
+    commands: Sender<ApplicationCommand>,
+    cursor_locked: Cell<bool>,
+    post_processing_preset: Cell<usize>,
+    cursor_position: Cell<[f32; 2]> // physical pixels
 }
 
 impl SceneLayer {
-    pub fn new(name: String) -> Self {
+    pub fn new(name: String, commands: Sender<ApplicationCommand>) -> Self {
+        let mut player = Sprite::new(0.0, 1.0, 5.0);
+        player.set_shader(flash_shader());
+        player.set_material_param("flash", MaterialValue::Float(0.0));
+        player.set_material_param("flash_color", MaterialValue::Color([1.0, 1.0, 1.0, 1.0]));
+        let minimap_texture = Rc::new(Texture::render_target(String::from("Minimap"), MINIMAP_TEXTURE_SIZE, MINIMAP_TEXTURE_SIZE));
+        let explored_texture = Rc::new(Texture {
+            sampler: SamplerOptions::PIXEL_ART,
+            ..Texture::from_pixels(String::from("Explored Map"), WORLD_SIZE, WORLD_SIZE, UNEXPLORED_COLOR.repeat((WORLD_SIZE * WORLD_SIZE) as usize))
+        });
+        let explored_region = TextureRegion::full(&explored_texture);
+
+        // warrior_idle.png holds a single frame, sheets with more frames per row are sliced the same way
+        let mut player_animator = Animator::new();
+        if let Some(texture) = player.get_renderable().material.texture.as_ref() {
+            let idle_frames = slice_grid(texture, [texture.width, texture.height]);
+            player_animator.add_clip(AnimationClip::from_regions(String::from("idle"), &idle_frames, Duration::from_millis(150), PlaybackMode::Loop));
+        }
+        if let Err(error) = player_animator.play("idle") {
+            warn!("{}", error);
+        }
+
+        // a small warrior circling around the player, attached to a pivot at the player's center
+        let mut transforms = TransformHierarchy::new();
+        let player_transform = transforms.add(player.get_renderable().transform.clone(), None);
+        let orbit_transform = transforms.add(Transform2D { pivot: [0.5, 0.5], ..Transform2D::default() }, Some(player_transform));
+        let orbiter_transform = transforms.add(Transform2D {
+            position: [0.8, 0.0],
+            scale: [0.3, 0.3],
+            pivot: [0.5, 0.5],
+            ..Transform2D::default()
+        }, Some(orbit_transform));
+
+        commands.send(ApplicationCommand::SetTextureMemoryBudget { window_id: None, budget: Some(TEXTURE_MEMORY_BUDGET) });
+
         Self {
             name,
-            player: RefCell::new(Sprite::new(0.0, 1.0, 5.0)),
+            player: RefCell::new(player),
+            player_animator: RefCell::new(player_animator),
+            player_flash: Cell::new(0.0),
             npcs: vec![
                 Sprite::new(2.0, 7.0, 2.0),
                 Sprite::new(7.0, 3.0, 1.0)
             ],
+            orbiter: Sprite::new(0.0, 0.0, 1.0),
+            transforms: RefCell::new(transforms),
+            player_transform,
+            orbit_transform,
+            orbiter_transform,
             camera: RefCell::new(Camera2D {
                 position: [0.0, 0.0],
                 size: [25.0, 25.0],
-                viewport_size: [1024, 768]
-            })
+                viewport_size: [1024, 768],
+                ..Camera2D::default()
+            }),
+            minimap_camera: RefCell::new(Camera2D {
+                size: [100.0, 100.0],
+                clear: ClearPolicy::Color([0.05, 0.05, 0.1, 1.0]),
+                scaling_mode: ScalingMode::Letterbox,
+                target: Some(Rc::clone(&minimap_texture)),
+                ..Camera2D::default()
+            }),
+            minimap_texture,
+            explored_map: Sprite::from_region(Rc::clone(&explored_texture), explored_region, WORLD_MIN, WORLD_MIN, 1.0),
+            explored_texture,
+            camera_follow: RefCell::new(FollowController::new([6.0, 4.0], 0.3, 0.25)),
+            camera_bounds: RefCell::new(BoundsController {
+                min: [-50.0, -50.0],
+                max: [50.0, 50.0]
+            }),
+            camera_shake: RefCell::new(CameraShake::new([1.0, 1.0], 0.05, 1.5, 15.0)),
+            commands,
+            cursor_locked: Cell::new(false),
+            post_processing_preset: Cell::new(0),
+            cursor_position: Cell::new([0.0, 0.0])
+        }
+    }
+
+    /// The texture the minimap camera renders to, for an overlay to show.
+    pub fn minimap_texture(&self) -> Rc<Texture> {
+        Rc::clone(&self.minimap_texture)
+    }
+
+    /// Lights up the pixel of the explored map below the player. Only that pixel is sent to the GPU,
+    /// the texture's own data keeps showing the unexplored world.
+    fn explore_player_position(&self) {
+        let [x, y] = self.player.borrow().get_renderable().transform.position;
+        let column = (x - WORLD_MIN).floor();
+        let row_from_bottom = (y - WORLD_MIN).floor();
+        if column < 0.0 || row_from_bottom < 0.0 || column >= WORLD_SIZE as f32 || row_from_bottom >= WORLD_SIZE as f32 {
+            return;
         }
+        let region = TextureRegion {
+            x: column as u32,
+            y: WORLD_SIZE - 1 - row_from_bottom as u32,
+            width: 1,
+            height: 1,
+            texture_size: [WORLD_SIZE, WORLD_SIZE]
+        };
+        self.commands.send(ApplicationCommand::SetTexturePixels {
+            window_id: None,
+            texture: Rc::clone(&self.explored_texture),
+            region: Some(region),
+            pixels: EXPLORED_COLOR.to_vec()
+        });
+    }
+
+    fn next_scaling_mode(&self) {
+        let mut camera = self.camera.borrow_mut();
+        camera.scaling_mode = match camera.scaling_mode {
+            ScalingMode::FitHeight => ScalingMode::Letterbox,
+            ScalingMode::Letterbox => ScalingMode::Expand,
+            ScalingMode::Expand => ScalingMode::Stretch,
+            ScalingMode::Stretch => ScalingMode::PixelPerfect { resolution: [200, 200] },
+            ScalingMode::PixelPerfect { .. } | ScalingMode::ScreenSpace { .. } => ScalingMode::FitHeight
+        };
+        info!("Camera scaling mode {:?}", camera.scaling_mode);
+    }
+
+    fn toggle_cursor_lock(&self) {
+        let locked = !self.cursor_locked.get();
+        self.cursor_locked.set(locked);
+
+        let mode = if locked { CursorGrabMode::Locked } else { CursorGrabMode::None };
+        self.commands.send(ApplicationCommand::SetCursorGrab { window_id: None, mode });
+        self.commands.send(ApplicationCommand::SetCursorVisible { window_id: None, visible: !locked });
+    }
+
+    /// Cycles through no effects, a soft glow, an old monitor and big pixels.
+    fn next_post_processing_preset(&self) {
+        let preset = (self.post_processing_preset.get() + 1) % 4;
+        self.post_processing_preset.set(preset);
+
+        let effects = match preset {
+            1 => vec![
+                PostEffect::Bloom { threshold: 0.7, intensity: 0.8, radius: 1.5 },
+                PostEffect::Vignette { intensity: 0.4, smoothness: 0.6 }
+            ],
+            2 => vec![PostEffect::Crt { scanline_intensity: 0.3, curvature: 0.08 }],
+            3 => vec![PostEffect::Pixelate { pixel_size: 6.0 }],
+            _ => vec![]
+        };
+        info!("Post-processing effects {:?}", effects);
+        self.commands.send(ApplicationCommand::SetPostProcessing { window_id: None, stack: PostProcessingStack { effects } });
     }
 }
 
 impl Layer for SceneLayer {
 
-    fn update(&self) {
+    fn update(&self, delta_time: Duration) {
         debug!("SampleLayer update");
+
+        let mut player_animator = self.player_animator.borrow_mut();
+        for event in player_animator.update(delta_time) {
+            debug!("Player animation: {:?}", event);
+        }
+        if let Some(region) = player_animator.current_region() {
+            self.player.borrow_mut().set_region(region);
+        }
+        let flash = (self.player_flash.get() - delta_time.as_secs_f32() * 4.0).max(0.0);
+        self.player_flash.set(flash);
+        self.player.borrow_mut().set_material_param("flash", MaterialValue::Float(flash));
+
+        let mut transforms = self.transforms.borrow_mut();
+        transforms.set_local(self.player_transform, self.player.borrow().get_renderable().transform.clone());
+        let mut orbit = transforms.local(self.orbit_transform).clone();
+        orbit.rotation += delta_time.as_secs_f32();
+        transforms.set_local(self.orbit_transform, orbit);
+
+        let mut camera = self.camera.borrow_mut();
+        let mut camera_follow = self.camera_follow.borrow_mut();
+        camera_follow.follow(&self.player.borrow().get_renderable().transform);
+        camera_follow.update(&mut camera, delta_time);
+        self.camera_bounds.borrow_mut().update(&mut camera, delta_time);
+        self.camera_shake.borrow_mut().update(&mut camera, delta_time);
+
+        // the minimap shows the whole world, centered on the same point as the main camera
+        self.minimap_camera.borrow_mut().set_center(camera.center());
     }
 
     fn handle_event(&self, event: &Event) -> bool {
@@ -49,22 +245,62 @@ impl Layer for SceneLayer {
                 self.camera.borrow_mut().update_viewport_size([*width, *height]);
                 true
             },
+            Event::ApplicationEvent(ScaleFactorChanged { scale_factor }) => {
+                self.camera.borrow_mut().update_scale_factor(*scale_factor);
+                self.minimap_camera.borrow_mut().update_scale_factor(*scale_factor);
+                true
+            },
+            Event::MouseEvent(MouseEvent::MouseMoved { x, y }) if !self.cursor_locked.get() => {
+                self.cursor_position.set([*x as f32, *y as f32]);
+                true
+            },
+            Event::MouseEvent(MouseEvent::MouseButtonPressed { button: MouseCode::ButtonLeft }) => {
+                let world_position = self.camera.borrow().screen_to_world(self.cursor_position.get());
+                info!("Clicked at world position {:?}", world_position);
+                true
+            },
+            Event::MouseEvent(MouseEvent::MouseScrolled { y_offset, .. }) => {
+                let mut camera = self.camera.borrow_mut();
+                camera.zoom = (camera.zoom * (1.0 + *y_offset as f32 * 0.01)).clamp(0.25, 4.0);
+                true
+            },
             Event::KeyboardEvent(KeyboardEvent::KeyPressed { key_code, is_repeat }) => {
                 match *key_code {
                     KeyCode::Up => {
                         self.player.borrow_mut().move_up(1.0);
+                        self.explore_player_position();
                         true
                     },
                     KeyCode::Down => {
                         self.player.borrow_mut().move_down(1.0);
+                        self.explore_player_position();
                         true
                     },
                     KeyCode::Left => {
                         self.player.borrow_mut().move_left(1.0);
+                        self.explore_player_position();
                         true
                     },
                     KeyCode::Right => {
                         self.player.borrow_mut().move_right(1.0);
+                        self.explore_player_position();
+                        true
+                    },
+                    KeyCode::Space => {
+                        self.camera_shake.borrow_mut().add_trauma(0.5);
+                        self.player_flash.set(1.0);
+                        true
+                    },
+                    KeyCode::M if !*is_repeat => {
+                        self.next_scaling_mode();
+                        true
+                    },
+                    KeyCode::C if !*is_repeat => {
+                        self.toggle_cursor_lock();
+                        true
+                    },
+                    KeyCode::P if !*is_repeat => {
+                        self.next_post_processing_preset();
                         true
                     },
                     _ => false
@@ -85,14 +321,23 @@ impl Layer for SceneLayer {
 
 impl Scene for SceneLayer {
     fn get_renderables(&self) -> Vec<Renderable> {
-        let mut renderables = self.npcs.iter().map(|go| {
+        let mut renderables = vec![self.explored_map.get_renderable().clone()];
+        renderables.extend(self.npcs.iter().map(|go| {
             go.get_renderable().clone()
-        }).collect::<Vec<_>>();
+        }));
         renderables.push(self.player.borrow().deref().get_renderable().clone());
+
+        let mut orbiter = self.orbiter.get_renderable().clone();
+        orbiter.world_matrix = Some(self.transforms.borrow().world_matrix(self.orbiter_transform));
+        renderables.push(orbiter);
         renderables
     }
 
-    fn get_camera(&self) -> Ref<Camera2D> {
+    fn get_camera(&self) -> Ref<'_, Camera2D> {
         self.camera.borrow()
     }
+
+    fn get_cameras(&self) -> Vec<Ref<'_, Camera2D>> {
+        vec![self.camera.borrow(), self.minimap_camera.borrow()]
+    }
 }
\ No newline at end of file
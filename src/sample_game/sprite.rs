@@ -1,8 +1,31 @@
+use std::rc::Rc;
 use crate::engine::renderer::{shaders, Renderable};
-use crate::engine::renderer::material::Material;
+use crate::engine::renderer::material::{Material, SamplerOptions, ShaderDefinition, Texture};
+use crate::engine::renderer::material_params::{MaterialParams, MaterialValue};
 use crate::engine::renderer::mesh::{Mesh, Vertex};
+use crate::engine::renderer::texture_region::TextureRegion;
 use crate::engine::renderer::transform::Transform2D;
-use crate::sample_game::resource_loader::load_texture_from_file;
+use crate::engine::resources::shader_loader::load_shader_from_file;
+use crate::engine::resources::texture_loader::load_texture_from_file;
+
+thread_local! {
+    /// Loaded once and shared by all sprites, so that it is uploaded to the GPU only once.
+    static WARRIOR_TEXTURE: Rc<Texture> = {
+        let mut texture = load_texture_from_file("src/sample_game/resources/warrior_idle.png", String::from("Warrior")).unwrap();
+        texture.sampler = SamplerOptions::PIXEL_ART;
+        texture.mipmaps = true; // the minimap draws it scaled down
+        Rc::new(texture)
+    };
+
+    /// A custom shader of the game, with the `flash` and `flash_color` material params.
+    /// Loaded at runtime, so that it is reloaded when the file is saved in debug builds.
+    static FLASH_SHADER: Rc<ShaderDefinition> =
+        Rc::new(load_shader_from_file("src/sample_game/flash_shader.wgsl", String::from("Flash Shader")).unwrap());
+}
+
+pub fn flash_shader() -> Rc<ShaderDefinition> {
+    FLASH_SHADER.with(Rc::clone)
+}
 
 #[derive(Debug, Clone)]
 pub struct Sprite {
@@ -16,10 +39,31 @@ impl Sprite {
     /// * `scale` - the scale in world units
     pub fn new(x: f32, y: f32, scale: f32) -> Self {
         let material = Material {
-            shader: &shaders::SPRITE_SHADER,
-            texture: Some(load_texture_from_file("src/sample_game/resources/warrior_idle.png", String::from("Warrior")).unwrap())
+            shader: shaders::sprite_shader(),
+            texture: Some(WARRIOR_TEXTURE.with(Rc::clone)),
+            region: None,
+            params: MaterialParams::default()
         };
-        
+        Self::with_material(material, x, y, [scale, scale])
+    }
+
+    /// A sprite that shows a part of a texture, e.g. a frame of a sprite sheet, sized after its pixels.
+    /// # Parameters
+    /// * `x` - position on the x-axis in world units
+    /// * `y` - position on the y-axis in world units
+    /// * `pixels_per_unit` - how many pixels of the texture fit in one world unit
+    pub fn from_region(texture: Rc<Texture>, region: TextureRegion, x: f32, y: f32, pixels_per_unit: f32) -> Self {
+        let scale = [region.width as f32 / pixels_per_unit, region.height as f32 / pixels_per_unit];
+        let material = Material {
+            shader: shaders::sprite_shader(),
+            texture: Some(texture),
+            region: Some(region),
+            params: MaterialParams::default()
+        };
+        Self::with_material(material, x, y, scale)
+    }
+
+    fn with_material(material: Material, x: f32, y: f32, scale: [f32; 2]) -> Self {
         let mesh = Mesh {
             name: String::from("Sprite Rectangle Mesh"),
             vertices: vec!(
@@ -70,8 +114,10 @@ impl Sprite {
             material,
             transform: Transform2D {
                 position: [x, y],
-                scale: [1.0 * scale, 1.0 * scale]
-            }
+                scale,
+                ..Transform2D::default()
+            },
+            world_matrix: None
         };
         
         Self {
@@ -82,7 +128,21 @@ impl Sprite {
     pub fn get_renderable(&self) -> &Renderable {
         &self.renderable
     }
+
+    /// Shows another part of the texture, e.g. the next frame of an animation.
+    pub fn set_region(&mut self, region: TextureRegion) {
+        self.renderable.material.region = Some(region);
+    }
     
+    pub fn set_shader(&mut self, shader: Rc<ShaderDefinition>) {
+        self.renderable.material.shader = shader;
+    }
+
+    /// Sets a value of the material that the sprite's shader reads, see `MaterialParams`.
+    pub fn set_material_param(&mut self, name: &str, value: MaterialValue) {
+        self.renderable.material.params.set(name, value);
+    }
+
     fn move_xy(&mut self, offset_x: f32, offset_y: f32) {
         self.renderable.transform = self.renderable.transform.position_to(self.renderable.transform.position[0] + offset_x, self.renderable.transform.position[1] + offset_y);
     }